@@ -0,0 +1,111 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use tempfile::TempDir;
+use the_quiz::question::Question;
+use the_quiz::source::{parse_data, serialize_questions_to_xml};
+use xml::reader::EventReader;
+
+fn questions(count: usize) -> Vec<Question> {
+    (0..count)
+        .map(|i| Question {
+            id: Some(format!("q{i}")),
+            text: format!("What is {i} + {i}?"),
+            answer: (i * 2).to_string(),
+            wrong_answers: vec![
+                (i * 2 + 1).to_string(),
+                (i * 2 + 2).to_string(),
+                (i * 3 + 3).to_string(),
+            ],
+            category: Some("Maths".to_string()),
+            ..Question::new()
+        })
+        .collect()
+}
+
+fn xml(count: usize) -> Vec<u8> {
+    let mut xml = Vec::new();
+    serialize_questions_to_xml(&questions(count), &mut xml).unwrap();
+    xml
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_data");
+    for count in [100, 1000, 10000] {
+        let input = xml(count);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &input, |b, input| {
+            b.iter(|| parse_data(EventReader::new(black_box(input.as_slice()))).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_shuffle(c: &mut Criterion) {
+    let pool = questions(1000);
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    c.bench_function("shuffle 1000 questions", |b| {
+        b.iter_batched_ref(
+            || pool.clone(),
+            |pool| pool.shuffle(&mut rng),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+/// Plays a game of the question file in the directory through `run_game`, answering from a
+/// queue of piped lines like the end-to-end tests do. The directory stands in for the home
+/// directory, so the history and leaderboard of whoever runs the benchmark stay untouched.
+fn play_game(dir: &Path, answers: &str) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_the_quiz"))
+        .current_dir(dir)
+        .env("HOME", dir)
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env("XDG_DATA_HOME", dir.join("data"))
+        .env("XDG_CACHE_HOME", dir.join("cache"))
+        .args([
+            "--source",
+            "file",
+            "--no-shuffle",
+            "--no-shuffle-questions",
+            "--seed",
+            "0",
+            "--no-color",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(answers.as_bytes())
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+fn bench_run_game(c: &mut Criterion) {
+    let mut group = c.benchmark_group("run_game");
+    group.sample_size(10);
+    for count in [100, 1000] {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("questions.xml"), xml(count)).unwrap();
+        //Without shuffling the correct answer is always the first option
+        let answers = "1\n".repeat(count);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(count),
+            &answers,
+            |b, answers| b.iter(|| play_game(dir.path(), answers)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_shuffle, bench_run_game);
+criterion_main!(benches);
@@ -0,0 +1,12 @@
+#![no_main]
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+use the_quiz::source::parse_data;
+use xml::reader::EventReader;
+
+//Any input may be rejected, but never by panicking
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_data(EventReader::new(Cursor::new(data)));
+});
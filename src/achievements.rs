@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+use chrono::Local;
+use crossterm::style::Color;
+
+use crate::dirs::app_dirs;
+use crate::game::GameSummary;
+use crate::history::CareerStats;
+use crate::styled_or_plain;
+
+const ACHIEVEMENTS_FILENAME: &str = "achievements.json";
+
+pub struct Achievement {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub condition: AchievementCondition,
+}
+
+/// What has to happen for an achievement to unlock.
+pub enum AchievementCondition {
+    /// Answer this many questions correctly in a row within one game.
+    StreakOf(u32),
+    /// Answer this many questions correctly across all games.
+    TotalCorrect(u32),
+    /// Answer every question of a game correctly.
+    PerfectGame,
+    /// Finish a game with at least one correct answer without taking a hint.
+    UsedNoHints,
+    /// Complete this many daily challenges.
+    CompletedDailyN(u32),
+}
+
+impl AchievementCondition {
+    fn is_met(&self, summary: &GameSummary, career: &CareerStats) -> bool {
+        match *self {
+            AchievementCondition::StreakOf(n) => summary.best_streak >= n,
+            AchievementCondition::TotalCorrect(n) => career.correct >= n,
+            AchievementCondition::PerfectGame => {
+                summary.correct > 0 && summary.correct as usize == summary.results.len()
+            }
+            AchievementCondition::UsedNoHints => summary.correct > 0 && summary.hints_used == 0,
+            AchievementCondition::CompletedDailyN(n) => career.dailies >= n,
+        }
+    }
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first_correct",
+        name: "First Steps",
+        description: "Answer your first question correctly.",
+        condition: AchievementCondition::TotalCorrect(1),
+    },
+    Achievement {
+        id: "no_hints",
+        name: "On My Own",
+        description: "Finish a game without taking a single hint.",
+        condition: AchievementCondition::UsedNoHints,
+    },
+    Achievement {
+        id: "streak_5",
+        name: "On a Roll",
+        description: "Answer 5 questions correctly in a row.",
+        condition: AchievementCondition::StreakOf(5),
+    },
+    Achievement {
+        id: "streak_10",
+        name: "Unstoppable",
+        description: "Answer 10 questions correctly in a row.",
+        condition: AchievementCondition::StreakOf(10),
+    },
+    Achievement {
+        id: "perfect_game",
+        name: "Flawless",
+        description: "Answer every question of a game correctly.",
+        condition: AchievementCondition::PerfectGame,
+    },
+    Achievement {
+        id: "total_100",
+        name: "Centurion",
+        description: "Answer 100 questions correctly across all games.",
+        condition: AchievementCondition::TotalCorrect(100),
+    },
+    Achievement {
+        id: "first_daily",
+        name: "Daily Player",
+        description: "Complete a daily challenge.",
+        condition: AchievementCondition::CompletedDailyN(1),
+    },
+    Achievement {
+        id: "daily_7",
+        name: "Creature of Habit",
+        description: "Complete 7 daily challenges.",
+        condition: AchievementCondition::CompletedDailyN(7),
+    },
+];
+
+fn achievements_path() -> io::Result<PathBuf> {
+    Ok(app_dirs().profile_dir()?.join(ACHIEVEMENTS_FILENAME))
+}
+
+/// The unlocked achievements by id, with the date they were unlocked on.
+fn load_unlocked() -> io::Result<BTreeMap<String, String>> {
+    let contents = match fs::read_to_string(achievements_path()?) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(err),
+    };
+    serde_json::from_str(&contents).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+}
+
+/// Unlocks the achievements the finished game earned and celebrates the new ones.
+pub fn update_achievements(summary: &GameSummary, career: &CareerStats, plain: bool) {
+    let result = load_unlocked().and_then(|mut unlocked| {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let mut new = Vec::new();
+        for achievement in ACHIEVEMENTS {
+            if !unlocked.contains_key(achievement.id)
+                && achievement.condition.is_met(summary, career)
+            {
+                unlocked.insert(achievement.id.to_string(), today.clone());
+                new.push(achievement);
+            }
+        }
+        if new.is_empty() {
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(&unlocked)
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+        fs::write(achievements_path()?, json)?;
+        for achievement in new {
+            println!(
+                "{} {} — {}",
+                styled_or_plain("Achievement unlocked!", Color::Yellow, plain),
+                achievement.name,
+                achievement.description
+            );
+        }
+        Ok(())
+    });
+    if let Err(err) = result {
+        println!("Warning: could not update the achievements: {err}");
+    }
+}
+
+/// Prints every achievement and whether it has been unlocked.
+pub fn print_achievements() {
+    let unlocked = match load_unlocked() {
+        Ok(unlocked) => unlocked,
+        Err(err) => {
+            println!("Could not read the achievements: {err}");
+            std::process::exit(1);
+        }
+    };
+    println!(
+        "{} of {} achievements unlocked",
+        unlocked.len(),
+        ACHIEVEMENTS.len()
+    );
+    for achievement in ACHIEVEMENTS {
+        let status = match unlocked.get(achievement.id) {
+            Some(date) => format!("[x] {date}"),
+            None => "[ ]".to_string(),
+        };
+        println!(
+            "{:<14} {:<18} {}",
+            status, achievement.name, achievement.description
+        );
+    }
+}
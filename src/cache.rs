@@ -0,0 +1,141 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::debug;
+
+use crate::dirs::app_dirs;
+use crate::question::Question;
+
+/// How long cached API responses are used when `--cache-ttl` isn't given.
+pub const DEFAULT_CACHE_TTL: u64 = 3600;
+const CACHE_SUBDIR: &str = "api";
+
+/// API responses kept in the cache directory, one file per request.
+///
+/// Each file starts with a line holding the Unix time it was written, followed by the
+/// questions as JSON.
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl Cache {
+    pub fn new(ttl: Duration) -> io::Result<Cache> {
+        Ok(Cache {
+            dir: cache_dir()?,
+            ttl,
+        })
+    }
+
+    /// The questions stored under the key, unless there are none or they are older than the TTL.
+    pub fn get(&self, key: &str) -> Option<Vec<Question>> {
+        let contents = fs::read_to_string(self.path(key)).ok()?;
+        let (written, json) = contents.split_once('\n')?;
+        let written = UNIX_EPOCH + Duration::from_secs(written.trim().parse().ok()?);
+        let age = SystemTime::now()
+            .duration_since(written)
+            .unwrap_or_default();
+        if age > self.ttl {
+            debug!(
+                "Cached response for {key} is {}s old, too old",
+                age.as_secs()
+            );
+            return None;
+        }
+        let questions = serde_json::from_str(json).ok()?;
+        debug!("Cache hit for {key}");
+        Some(questions)
+    }
+
+    pub fn set(&self, key: &str, questions: &[Question]) -> io::Result<()> {
+        let json = serde_json::to_string(questions)
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        fs::write(self.path(key), format!("{now}\n{json}"))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", fnv1a(key)))
+    }
+}
+
+fn cache_dir() -> io::Result<PathBuf> {
+    let dir = app_dirs().cache_dir()?.join(CACHE_SUBDIR);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Deletes every cached response and returns how many there were.
+pub fn clear_cache() -> io::Result<usize> {
+    let dir = app_dirs().cache_dir()?.join(CACHE_SUBDIR);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err),
+    };
+    let mut removed = 0;
+    for entry in entries {
+        fs::remove_file(entry?.path())?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// A hash that stays the same across Rust versions, so file names keep matching their keys.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn cache(dir: &TempDir) -> Cache {
+        Cache {
+            dir: dir.path().to_path_buf(),
+            ttl: Duration::from_secs(DEFAULT_CACHE_TTL),
+        }
+    }
+
+    fn question() -> Question {
+        Question {
+            text: "What is the capital of Australia?".to_string(),
+            answer: "Canberra".to_string(),
+            wrong_answers: vec!["Sydney".to_string()],
+            ..Question::new()
+        }
+    }
+
+    #[test]
+    fn returns_what_was_stored() {
+        let dir = TempDir::new().unwrap();
+        let cache = cache(&dir);
+        cache
+            .set("https://example.com?limit=1", &[question()])
+            .unwrap();
+        assert_eq!(
+            cache.get("https://example.com?limit=1"),
+            Some(vec![question()])
+        );
+        assert_eq!(cache.get("https://example.com?limit=2"), None);
+    }
+
+    #[test]
+    fn ignores_responses_older_than_the_ttl() {
+        let dir = TempDir::new().unwrap();
+        let cache = cache(&dir);
+        let key = "https://example.com?limit=1";
+        let json = serde_json::to_string(&[question()]).unwrap();
+        fs::write(cache.path(key), format!("0\n{json}")).unwrap();
+        assert_eq!(cache.get(key), None);
+    }
+}
@@ -0,0 +1,424 @@
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+
+use clap::ValueEnum;
+use crossterm::event::KeyCode;
+use serde::Deserialize;
+
+use crate::dirs::app_dirs;
+use crate::question::Difficulty;
+use crate::source::Source;
+
+const CONFIG_FILENAME: &str = "config.toml";
+const DEFAULT_HINT_PENALTY: u32 = 3;
+
+/// Written to the config directory on first run so there is something to edit.
+const DEFAULT_CONFIG: &str = r#"# Settings for the_quiz. Uncomment a line to change it.
+# Command line flags take precedence over anything set here.
+
+[defaults]
+# Where the questions come from: "file", "web", "anki" or "stdin" (see --help for
+# --source). Asks every time if unset.
+# source = "web"
+# How many questions to play.
+# limit = 10
+# How hard the questions from the web should be, "easy", "medium" or "hard".
+# difficulty = "medium"
+# Set to false to print everything without colors.
+# color = true
+# Set to true to ring the terminal bell after each answer.
+# sound = false
+# Seconds to answer each question.
+# timer = 30
+# How many tries each question gets before the answer is shown.
+# attempts = 1
+
+[keybindings]
+# The keys for the answers and the other keys that go with them: "numbers" (1 to 9),
+# "letters" (a to g) or "home-row" (a s d f j k l ;, skip moves to n).
+# preset = "numbers"
+# Keys set below win over the preset.
+# answers = ["1", "2", "3", "4", "5", "6", "7", "8", "9"]
+# skip = "s"
+# hint = "h"
+# pause = "p"
+# quit = "q"
+
+[scoring]
+# Points taken off a correct answer for each hint.
+# hint_penalty = 3
+
+[api]
+# Sent as X-Api-Key with every request. QUIZ_API_KEY takes precedence.
+# key = "..."
+# Seconds to wait for an answer from the API.
+# timeout = 10
+"#;
+
+/// Settings read from `config.toml` in the config directory.
+#[derive(Clone)]
+pub struct Config {
+    pub source: Option<Source>,
+    pub limit: Option<usize>,
+    pub difficulty: Option<Difficulty>,
+    pub color: bool,
+    /// Ring the terminal bell after each answer.
+    pub sound: bool,
+    /// Seconds to answer each question.
+    pub timer: Option<u64>,
+    /// How many tries each question gets.
+    pub attempts: Option<u32>,
+    pub keybindings: KeyBindings,
+    /// Points taken off a correct answer for each hint used on it.
+    pub hint_penalty: u32,
+    /// Sent as `X-Api-Key` with every API request. Taken from `QUIZ_API_KEY` if that is set.
+    pub api_key: Option<String>,
+    /// Seconds to wait for an answer from the API.
+    pub api_timeout: Option<u64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            source: None,
+            limit: None,
+            difficulty: None,
+            color: true,
+            sound: false,
+            timer: None,
+            attempts: None,
+            keybindings: KeyBindings::default(),
+            hint_penalty: DEFAULT_HINT_PENALTY,
+            api_key: None,
+            api_timeout: None,
+        }
+    }
+}
+
+//Written by hand so the API key never ends up in logs
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("source", &self.source)
+            .field("limit", &self.limit)
+            .field("difficulty", &self.difficulty)
+            .field("color", &self.color)
+            .field("sound", &self.sound)
+            .field("timer", &self.timer)
+            .field("attempts", &self.attempts)
+            .field("keybindings", &self.keybindings)
+            .field("hint_penalty", &self.hint_penalty)
+            .field("api_key", &self.api_key.as_ref().map(|_| "[REDACTED]"))
+            .field("api_timeout", &self.api_timeout)
+            .finish()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawConfig {
+    defaults: RawDefaults,
+    keybindings: RawKeyBindings,
+    scoring: RawScoring,
+    api: RawApi,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawDefaults {
+    source: Option<Source>,
+    limit: Option<usize>,
+    difficulty: Option<Difficulty>,
+    color: Option<bool>,
+    sound: Option<bool>,
+    timer: Option<u64>,
+    attempts: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawScoring {
+    hint_penalty: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawApi {
+    key: Option<String>,
+    timeout: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawKeyBindings {
+    preset: Option<KeyPreset>,
+    answers: Option<Vec<String>>,
+    skip: Option<String>,
+    hint: Option<String>,
+    pause: Option<String>,
+    quit: Option<String>,
+}
+
+/// Something the player can do by pressing a key while a question is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Answer(usize),
+    Skip,
+    Hint,
+    Pause,
+    Quit,
+}
+
+/// Ready-made sets of answer keys, along with other keys that don't clash with them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyPreset {
+    /// 1 to 9
+    #[default]
+    Numbers,
+    /// a to g
+    Letters,
+    /// a s d f j k l ; with skip on n
+    HomeRow,
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyBindings {
+    pub answers: Vec<KeyCode>,
+    pub skip: KeyCode,
+    pub hint: KeyCode,
+    pub pause: KeyCode,
+    pub quit: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings::preset(KeyPreset::Numbers)
+    }
+}
+
+impl KeyBindings {
+    pub fn preset(preset: KeyPreset) -> KeyBindings {
+        let (answers, skip) = match preset {
+            KeyPreset::Numbers => ("123456789", 's'),
+            KeyPreset::Letters => ("abcdefg", 's'),
+            KeyPreset::HomeRow => ("asdfjkl;", 'n'),
+        };
+        KeyBindings {
+            answers: answers.chars().map(KeyCode::Char).collect(),
+            skip: KeyCode::Char(skip),
+            hint: KeyCode::Char('h'),
+            pause: KeyCode::Char('p'),
+            quit: KeyCode::Char('q'),
+        }
+    }
+
+    /// Looks up which action, if any, a key press is bound to.
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        let code = normalize(code);
+        if let Some(index) = self.answers.iter().position(|key| *key == code) {
+            return Some(Action::Answer(index));
+        }
+        if code == self.skip {
+            return Some(Action::Skip);
+        }
+        if code == self.hint {
+            return Some(Action::Hint);
+        }
+        if code == self.pause {
+            return Some(Action::Pause);
+        }
+        //Esc always ends the game early, unless it is bound to something else
+        if code == self.quit || code == KeyCode::Esc {
+            return Some(Action::Quit);
+        }
+        None
+    }
+
+    /// The label shown next to an answer option.
+    pub fn answer_label(&self, index: usize) -> String {
+        match self.answers.get(index) {
+            Some(key) => key_name(*key),
+            None => String::from("-"),
+        }
+    }
+
+    fn from_raw(raw: RawKeyBindings) -> Result<KeyBindings, String> {
+        let defaults = KeyBindings::preset(raw.preset.unwrap_or_default());
+        let bindings = KeyBindings {
+            answers: match raw.answers {
+                Some(keys) => keys
+                    .iter()
+                    .map(|key| parse_key(key))
+                    .collect::<Result<_, _>>()?,
+                None => defaults.answers,
+            },
+            skip: match raw.skip {
+                Some(key) => parse_key(&key)?,
+                None => defaults.skip,
+            },
+            hint: match raw.hint {
+                Some(key) => parse_key(&key)?,
+                None => defaults.hint,
+            },
+            pause: match raw.pause {
+                Some(key) => parse_key(&key)?,
+                None => defaults.pause,
+            },
+            quit: match raw.quit {
+                Some(key) => parse_key(&key)?,
+                None => defaults.quit,
+            },
+        };
+        bindings.validate()?;
+        Ok(bindings)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut bound: Vec<(KeyCode, String)> = Vec::new();
+        let actions = self
+            .answers
+            .iter()
+            .enumerate()
+            .map(|(index, key)| (*key, format!("answer {}", index + 1)))
+            .chain([
+                (self.skip, String::from("skip")),
+                (self.hint, String::from("hint")),
+                (self.pause, String::from("pause")),
+                (self.quit, String::from("quit")),
+            ]);
+        for (key, action) in actions {
+            if let Some((_, other)) = bound.iter().find(|(k, _)| *k == key) {
+                return Err(format!(
+                    "Key '{}' is bound to both {other} and {action}.",
+                    key_name(key)
+                ));
+            }
+            bound.push((key, action));
+        }
+        Ok(())
+    }
+}
+
+/// Parses a key string like `"s"`, `"esc"` or `"enter"`.
+fn parse_key(key: &str) -> Result<KeyCode, String> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(normalize(KeyCode::Char(c)));
+    }
+    match key.to_lowercase().as_str() {
+        "space" => Ok(KeyCode::Char(' ')),
+        "enter" => Ok(KeyCode::Enter),
+        "esc" | "escape" => Ok(KeyCode::Esc),
+        "tab" => Ok(KeyCode::Tab),
+        "backspace" => Ok(KeyCode::Backspace),
+        _ => Err(format!("Unknown key '{key}'.")),
+    }
+}
+
+fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => String::from("Space"),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => String::from("Enter"),
+        KeyCode::Esc => String::from("Esc"),
+        KeyCode::Tab => String::from("Tab"),
+        KeyCode::Backspace => String::from("Backspace"),
+        other => format!("{other:?}"),
+    }
+}
+
+/// Letters are matched case-insensitively.
+fn normalize(code: KeyCode) -> KeyCode {
+    match code {
+        KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+        other => other,
+    }
+}
+
+/// Loads the config file, falling back to the defaults if there is none.
+pub fn load_config() -> Config {
+    let mut config = load_config_file();
+    if let Ok(key) = std::env::var("QUIZ_API_KEY") {
+        config.api_key = Some(key);
+    }
+    config
+}
+
+fn load_config_file() -> Config {
+    let path = match app_dirs().config_dir() {
+        Ok(dir) => dir.join(CONFIG_FILENAME),
+        Err(err) => {
+            println!("Could not create the config directory: {err}");
+            return Config::default();
+        }
+    };
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            if let Err(err) = fs::write(&path, DEFAULT_CONFIG) {
+                println!("Could not create {}: {err}", path.display());
+            }
+            return Config::default();
+        }
+        Err(err) => {
+            println!("Could not read {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    };
+    let raw: RawConfig = match toml::from_str(&contents) {
+        Ok(raw) => raw,
+        Err(err) => {
+            println!("Error in {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    };
+    let keybindings = match KeyBindings::from_raw(raw.keybindings) {
+        Ok(keybindings) => keybindings,
+        Err(err) => {
+            println!("Error in the keybindings of {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    };
+
+    Config {
+        source: raw.defaults.source,
+        limit: raw.defaults.limit,
+        difficulty: raw.defaults.difficulty,
+        color: raw.defaults.color.unwrap_or(true),
+        sound: raw.defaults.sound.unwrap_or(false),
+        timer: raw.defaults.timer,
+        attempts: raw.defaults.attempts,
+        keybindings,
+        hint_penalty: raw.scoring.hint_penalty.unwrap_or(DEFAULT_HINT_PENALTY),
+        api_key: raw.api.key,
+        api_timeout: raw.api.timeout,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_dont_bind_a_key_twice() {
+        for preset in KeyPreset::value_variants() {
+            KeyBindings::preset(*preset).validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn keys_in_the_config_win_over_the_preset() {
+        let raw: RawKeyBindings = toml::from_str(
+            r#"
+            preset = "home-row"
+            skip = "x"
+            "#,
+        )
+        .unwrap();
+        let bindings = KeyBindings::from_raw(raw).unwrap();
+        assert_eq!(bindings.action(KeyCode::Char('s')), Some(Action::Answer(1)));
+        assert_eq!(bindings.action(KeyCode::Char('x')), Some(Action::Skip));
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use directories::ProjectDirs;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// The profile chosen with `--profile`, set once at startup.
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// The platform specific directories the quiz keeps its files in.
+pub struct AppDirs {
+    data: PathBuf,
+    config: PathBuf,
+    cache: PathBuf,
+}
+
+impl AppDirs {
+    pub fn data_dir(&self) -> io::Result<&Path> {
+        ensure_dir(&self.data)
+    }
+
+    pub fn config_dir(&self) -> io::Result<&Path> {
+        ensure_dir(&self.config)
+    }
+
+    pub fn cache_dir(&self) -> io::Result<&Path> {
+        ensure_dir(&self.cache)
+    }
+
+    /// The directory holding every profile.
+    pub fn profiles_dir(&self) -> PathBuf {
+        self.data.join("profiles")
+    }
+
+    /// The directory of the active profile, where its history and stats are kept.
+    pub fn profile_dir(&self) -> io::Result<PathBuf> {
+        let dir = self.profiles_dir().join(active_profile());
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// The path of a file in the directory of the active profile.
+    ///
+    /// Before there were profiles the file was kept in the data directory. The default profile
+    /// takes it over from there the first time it is asked for.
+    pub fn profile_file(&self, name: &str) -> io::Result<PathBuf> {
+        let path = self.profile_dir()?.join(name);
+        let old = self.data.join(name);
+        if active_profile() == DEFAULT_PROFILE && !path.exists() && old.is_file() {
+            fs::rename(old, &path)?;
+        }
+        Ok(path)
+    }
+}
+
+/// Chooses the profile for this run. Only the first call has an effect.
+pub fn set_profile(name: &str) {
+    let _ = PROFILE.set(name.to_string());
+}
+
+pub fn active_profile() -> &'static str {
+    PROFILE.get().map_or(DEFAULT_PROFILE, String::as_str)
+}
+
+/// Returns the data, config and cache directories for the current OS.
+///
+/// If no home directory can be found everything falls back to the working directory.
+pub fn app_dirs() -> AppDirs {
+    match ProjectDirs::from("", "", "the_quiz") {
+        Some(dirs) => AppDirs {
+            data: dirs.data_dir().to_path_buf(),
+            config: dirs.config_dir().to_path_buf(),
+            cache: dirs.cache_dir().to_path_buf(),
+        },
+        None => AppDirs {
+            data: PathBuf::from("."),
+            config: PathBuf::from("."),
+            cache: PathBuf::from("."),
+        },
+    }
+}
+
+fn ensure_dir(dir: &Path) -> io::Result<&Path> {
+    fs::create_dir_all(dir)?;
+    Ok(dir)
+}
@@ -0,0 +1,386 @@
+use std::io::{self, stdout, Stdout, Write};
+use std::path::Path;
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{
+    self, disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+    LeaveAlternateScreen,
+};
+use crossterm::{execute, queue};
+
+use crate::question::Question;
+use crate::source::{get_questions_from_path, save_questions_to_path};
+use crate::validate::validate_questions;
+
+/// Puts the terminal back the way it was when the editor ends, even on a panic.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<TerminalGuard> {
+        enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen, Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), Show, LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Id,
+    Prompt,
+    Correct,
+    Wrong,
+    Source,
+}
+
+impl FieldKind {
+    fn label(self) -> &'static str {
+        match self {
+            FieldKind::Id => "Id",
+            FieldKind::Prompt => "Prompt",
+            FieldKind::Correct => "Correct answer",
+            FieldKind::Wrong => "Wrong answer",
+            FieldKind::Source => "Source",
+        }
+    }
+}
+
+struct Field {
+    kind: FieldKind,
+    value: String,
+}
+
+enum Mode {
+    List,
+    /// Editing the fields of the selected question.
+    Form {
+        fields: Vec<Field>,
+        focus: usize,
+    },
+}
+
+struct Editor {
+    questions: Vec<Question>,
+    selected: usize,
+    /// The first question shown in the list.
+    scroll: usize,
+    mode: Mode,
+    status: String,
+    unsaved: bool,
+    /// Set after the first `q` with unsaved changes, a second one quits anyway.
+    confirm_quit: bool,
+}
+
+/// Opens the full-screen editor for a question file. A file that doesn't exist yet is created on save.
+pub fn run_editor(path: &Path) {
+    let questions = if path.exists() {
+        get_questions_from_path(path, false)
+    } else {
+        Vec::new()
+    };
+    let mut editor = Editor {
+        questions,
+        selected: 0,
+        scroll: 0,
+        mode: Mode::List,
+        status: String::from(LIST_HELP),
+        unsaved: false,
+        confirm_quit: false,
+    };
+    if let Err(err) = editor.run(path) {
+        println!("The editor failed: {err}");
+        std::process::exit(1);
+    }
+}
+
+const LIST_HELP: &str = "↑/↓ select  Enter edit  a add  d delete  Ctrl-S save  q quit";
+const FORM_HELP: &str = "↑/↓ field  type to edit  Esc back to the list  Ctrl-S save";
+
+impl Editor {
+    fn run(&mut self, path: &Path) -> io::Result<()> {
+        let _guard = TerminalGuard::enter()?;
+        let mut out = stdout();
+        loop {
+            self.draw(&mut out, path)?;
+            let Event::Key(KeyEvent { code, modifiers }) = read()? else {
+                continue;
+            };
+            if code == KeyCode::Char('s') && modifiers.contains(KeyModifiers::CONTROL) {
+                self.save(path);
+                continue;
+            }
+            let quit = match self.mode {
+                Mode::List => self.list_key(code),
+                Mode::Form { .. } => {
+                    self.form_key(code);
+                    false
+                }
+            };
+            if quit {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Handles a key in the list. Returns whether the editor should close.
+    fn list_key(&mut self, code: KeyCode) -> bool {
+        let quitting = self.confirm_quit;
+        self.confirm_quit = false;
+        match code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                if self.selected + 1 < self.questions.len() {
+                    self.selected += 1;
+                }
+            }
+            KeyCode::Enter if !self.questions.is_empty() => self.open_form(),
+            KeyCode::Char('a') => {
+                self.questions.push(Question::new());
+                self.selected = self.questions.len() - 1;
+                self.unsaved = true;
+                self.open_form();
+            }
+            KeyCode::Char('d') if !self.questions.is_empty() => {
+                self.questions.remove(self.selected);
+                self.selected = self.selected.min(self.questions.len().saturating_sub(1));
+                self.unsaved = true;
+                self.status = String::from("Question deleted.");
+            }
+            KeyCode::Char('q') | KeyCode::Esc => {
+                if !self.unsaved || quitting {
+                    return true;
+                }
+                self.confirm_quit = true;
+                self.status = String::from("There are unsaved changes. Press q again to quit.");
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn form_key(&mut self, code: KeyCode) {
+        let Mode::Form { fields, focus } = &mut self.mode else {
+            return;
+        };
+        match code {
+            KeyCode::Up => *focus = focus.saturating_sub(1),
+            KeyCode::Down | KeyCode::Enter | KeyCode::Tab => {
+                if *focus + 1 < fields.len() {
+                    *focus += 1;
+                }
+            }
+            KeyCode::Char(c) => {
+                fields[*focus].value.push(c);
+                self.unsaved = true;
+            }
+            KeyCode::Backspace => {
+                fields[*focus].value.pop();
+                self.unsaved = true;
+            }
+            KeyCode::Esc => {
+                self.close_form();
+                return;
+            }
+            _ => {}
+        }
+        add_empty_slots(fields);
+    }
+
+    fn open_form(&mut self) {
+        let question = &self.questions[self.selected];
+        let mut fields = vec![
+            Field {
+                kind: FieldKind::Id,
+                value: question.id.clone().unwrap_or_default(),
+            },
+            Field {
+                kind: FieldKind::Prompt,
+                value: question.text.clone(),
+            },
+        ];
+        for answer in question
+            .correct_answers()
+            .into_iter()
+            .filter(|a| !a.is_empty())
+        {
+            fields.push(Field {
+                kind: FieldKind::Correct,
+                value: answer.to_string(),
+            });
+        }
+        for answer in &question.wrong_answers {
+            fields.push(Field {
+                kind: FieldKind::Wrong,
+                value: answer.clone(),
+            });
+        }
+        fields.push(Field {
+            kind: FieldKind::Source,
+            value: question.source.clone().unwrap_or_default(),
+        });
+        add_empty_slots(&mut fields);
+        self.mode = Mode::Form { fields, focus: 1 };
+        self.status = String::from(FORM_HELP);
+    }
+
+    /// Writes the fields back into the question and returns to the list.
+    fn close_form(&mut self) {
+        let Mode::Form { fields, .. } = std::mem::replace(&mut self.mode, Mode::List) else {
+            return;
+        };
+        let question = &mut self.questions[self.selected];
+        let values = |kind: FieldKind| -> Vec<String> {
+            fields
+                .iter()
+                .filter(|field| field.kind == kind && !field.value.trim().is_empty())
+                .map(|field| field.value.trim().to_string())
+                .collect()
+        };
+        let optional = |kind: FieldKind| values(kind).into_iter().next();
+        question.id = optional(FieldKind::Id);
+        question.text = optional(FieldKind::Prompt).unwrap_or_default();
+        let mut correct = values(FieldKind::Correct).into_iter();
+        question.answer = correct.next().unwrap_or_default();
+        question.extra_answers = correct.collect();
+        question.wrong_answers = values(FieldKind::Wrong);
+        question.source = optional(FieldKind::Source);
+        self.status = String::from(LIST_HELP);
+    }
+
+    fn save(&mut self, path: &Path) {
+        if let Mode::Form { .. } = self.mode {
+            self.close_form();
+        }
+        let issues = validate_questions(&self.questions);
+        if let Some(issue) = issues.first() {
+            self.selected = issue.index;
+            self.status = format!("Not saved: question {} {}.", issue.index + 1, issue.message);
+            return;
+        }
+        match save_questions_to_path(path, &self.questions) {
+            Ok(()) => {
+                self.unsaved = false;
+                self.status = format!("Saved {} questions.", self.questions.len());
+            }
+            Err(err) => self.status = format!("Could not save: {err}"),
+        }
+    }
+
+    fn draw(&mut self, out: &mut Stdout, path: &Path) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+        let title = format!(
+            "{}{}",
+            path.display(),
+            if self.unsaved { " (unsaved)" } else { "" }
+        );
+        draw_line(out, 0, &title, width, true)?;
+
+        //Two rows for the title and the status line
+        let rows = height.saturating_sub(2);
+        match &self.mode {
+            Mode::List => {
+                if self.selected < self.scroll {
+                    self.scroll = self.selected;
+                } else if self.selected >= self.scroll + rows {
+                    self.scroll = self.selected + 1 - rows;
+                }
+                if self.questions.is_empty() {
+                    draw_line(
+                        out,
+                        1,
+                        "No questions yet, press a to add one.",
+                        width,
+                        false,
+                    )?;
+                }
+                for (row, (index, question)) in self
+                    .questions
+                    .iter()
+                    .enumerate()
+                    .skip(self.scroll)
+                    .take(rows)
+                    .enumerate()
+                {
+                    let text = format!("{:>4}. {}", index + 1, question.text);
+                    draw_line(out, row + 1, &text, width, index == self.selected)?;
+                }
+            }
+            Mode::Form { fields, focus } => {
+                let first = (focus + 1).saturating_sub(rows);
+                for (row, (index, field)) in
+                    fields.iter().enumerate().skip(first).take(rows).enumerate()
+                {
+                    let text = format!("{:>15}: {}", field.kind.label(), field.value);
+                    draw_line(out, row + 1, &text, width, index == *focus)?;
+                }
+            }
+        }
+        draw_line(out, height.saturating_sub(1), &self.status, width, false)?;
+        out.flush()
+    }
+}
+
+/// Keeps one empty correct and wrong answer field around, to type new answers into.
+fn add_empty_slots(fields: &mut Vec<Field>) {
+    for kind in [FieldKind::Correct, FieldKind::Wrong] {
+        let has_empty = fields
+            .iter()
+            .any(|field| field.kind == kind && field.value.is_empty());
+        if !has_empty {
+            let position = fields
+                .iter()
+                .rposition(|field| field.kind == kind)
+                .map_or_else(|| slot_position(fields, kind), |last| last + 1);
+            fields.insert(
+                position,
+                Field {
+                    kind,
+                    value: String::new(),
+                },
+            );
+        }
+    }
+}
+
+/// Where the first field of a kind goes when the form has none yet.
+fn slot_position(fields: &[Field], kind: FieldKind) -> usize {
+    let after = match kind {
+        FieldKind::Wrong => FieldKind::Correct,
+        _ => FieldKind::Prompt,
+    };
+    fields
+        .iter()
+        .rposition(|field| field.kind == after)
+        .map_or(fields.len(), |last| last + 1)
+}
+
+fn draw_line(
+    out: &mut Stdout,
+    row: usize,
+    text: &str,
+    width: usize,
+    highlight: bool,
+) -> io::Result<()> {
+    let text: String = text.chars().take(width).collect();
+    queue!(out, MoveTo(0, row as u16))?;
+    if highlight {
+        queue!(
+            out,
+            SetAttribute(Attribute::Reverse),
+            Print(text),
+            SetAttribute(Attribute::Reset)
+        )
+    } else {
+        queue!(out, Print(text))
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+use std::io;
+
+/// Errors of the operations that report failures instead of ending the program.
+#[derive(Debug)]
+pub enum QuizError {
+    Io(io::Error),
+    XmlWrite(xml::writer::Error),
+    Zip(zip::result::ZipError),
+    Sqlite(rusqlite::Error),
+    /// An Anki deck that is missing something it needs.
+    Anki(String),
+    Http(reqwest::Error),
+    /// An API response that doesn't hold questions.
+    Api(String),
+}
+
+impl fmt::Display for QuizError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuizError::Io(err) => write!(f, "{err}"),
+            QuizError::XmlWrite(err) => write!(f, "could not write XML: {err}"),
+            QuizError::Zip(err) => write!(f, "could not read the archive: {err}"),
+            QuizError::Sqlite(err) => write!(f, "could not read the database: {err}"),
+            QuizError::Anki(message) => write!(f, "{message}"),
+            QuizError::Http(err) => write!(f, "Error on download: {err}"),
+            QuizError::Api(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for QuizError {}
+
+impl From<io::Error> for QuizError {
+    fn from(err: io::Error) -> Self {
+        QuizError::Io(err)
+    }
+}
+
+impl From<xml::writer::Error> for QuizError {
+    fn from(err: xml::writer::Error) -> Self {
+        QuizError::XmlWrite(err)
+    }
+}
+
+impl From<zip::result::ZipError> for QuizError {
+    fn from(err: zip::result::ZipError) -> Self {
+        QuizError::Zip(err)
+    }
+}
+
+impl From<rusqlite::Error> for QuizError {
+    fn from(err: rusqlite::Error) -> Self {
+        QuizError::Sqlite(err)
+    }
+}
+
+impl From<reqwest::Error> for QuizError {
+    fn from(err: reqwest::Error) -> Self {
+        QuizError::Http(err)
+    }
+}
@@ -0,0 +1,138 @@
+use std::fs::OpenOptions;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, SecondsFormat};
+use serde::Serialize;
+
+use crate::game::{GameSummary, Outcome, QuestionResult};
+
+/// The record of a whole game written by `--export`.
+#[derive(Serialize)]
+pub struct SessionResult<'a> {
+    pub finished_at: String,
+    pub source: &'a str,
+    pub correct: u32,
+    pub incorrect: u32,
+    pub points: u32,
+    pub duration_secs: u64,
+    pub questions: Vec<QuestionRecord<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct QuestionRecord<'a> {
+    pub id: Option<&'a str>,
+    pub question: &'a str,
+    pub correct_answers: Vec<&'a str>,
+    pub answer: &'a [String],
+    pub outcome: Outcome,
+    pub correct: bool,
+    pub time_ms: u128,
+}
+
+impl<'a> SessionResult<'a> {
+    pub fn new(summary: &'a GameSummary) -> SessionResult<'a> {
+        SessionResult {
+            finished_at: Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+            source: &summary.source,
+            correct: summary.correct,
+            incorrect: summary.incorrect,
+            points: summary.points,
+            duration_secs: summary.duration.as_secs(),
+            questions: summary
+                .results
+                .iter()
+                .map(|result| QuestionRecord {
+                    id: result.question.id.as_deref(),
+                    question: &result.question.text,
+                    correct_answers: result.question.correct_answers(),
+                    answer: &result.chosen,
+                    outcome: result.outcome,
+                    correct: result.outcome == Outcome::Correct,
+                    time_ms: result.time.as_millis(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Writes the game to a JSON file. An existing file is kept and the export gets a timestamped name instead.
+pub fn export_json(path: &Path, summary: &GameSummary) {
+    let path = if path.exists() {
+        let timestamped = timestamped_path(path);
+        println!(
+            "{} already exists, exporting to {} instead.",
+            path.display(),
+            timestamped.display()
+        );
+        timestamped
+    } else {
+        path.to_path_buf()
+    };
+    let result = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .and_then(|file| {
+            serde_json::to_writer_pretty(BufWriter::new(file), &SessionResult::new(summary))
+                .map_err(io::Error::from)
+        });
+    match result {
+        Ok(()) => println!("Exported the results to {}", path.display()),
+        Err(err) => println!("Could not export the results to {}: {err}", path.display()),
+    }
+}
+
+/// `results.json` becomes `results-20240131-154502.json`.
+fn timestamped_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut name = format!("{stem}-{}", Local::now().format("%Y%m%d-%H%M%S"));
+    if let Some(extension) = path.extension() {
+        name.push('.');
+        name.push_str(&extension.to_string_lossy());
+    }
+    path.with_file_name(name)
+}
+
+/// Writes one row per question of the game to a CSV file, replacing whatever was there.
+pub fn export_csv(path: &Path, results: &[QuestionResult]) {
+    if path.exists() {
+        println!("Warning: overwriting {}", path.display());
+    }
+    if let Err(err) = write_csv(path, results) {
+        println!("Could not export the results to {}: {err}", path.display());
+    }
+}
+
+fn write_csv(path: &Path, results: &[QuestionResult]) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record([
+        "question_text",
+        "correct_answer",
+        "user_answer",
+        "was_correct",
+        "time_taken_ms",
+        "difficulty",
+        "category",
+    ])?;
+    for result in results {
+        let question = &result.question;
+        writer.write_record([
+            question.text.clone(),
+            question.correct_answers().join(", "),
+            result.chosen.join(", "),
+            (result.outcome == Outcome::Correct).to_string(),
+            result.time.as_millis().to_string(),
+            question
+                .difficulty
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+            question.category.clone().unwrap_or_default(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
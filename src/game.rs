@@ -0,0 +1,1393 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{stdout, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use crossterm::cursor::{MoveTo, MoveToPreviousLine};
+use crossterm::event::KeyCode;
+use crossterm::execute;
+use crossterm::style::{Attribute, Color};
+use crossterm::terminal::{Clear, ClearType};
+use rand::seq::index::sample;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+use strsim::levenshtein;
+
+use crate::config::{Action, Config};
+use crate::input::{read_key, read_key_before, read_line, InputSource, TimedKey};
+use crate::messages::{fill, text, Msg};
+use crate::question::{normalize_answer, Difficulty, Question};
+use crate::question_stats::load_mastery;
+use crate::save::{delete_saved_game, save_game, track_game, untrack_game, GameState, PlayerScore};
+use crate::signals::terminate_requested;
+use crate::source::{QuestionSource, NO_QUESTIONS_EXIT_CODE};
+use crate::spaced_repetition::sort_by_due;
+use crate::{emphasized_or_plain, styled_or_plain, Args};
+
+/// Points awarded for a correct answer before hint penalties.
+const POINTS_PER_QUESTION: u32 = 10;
+/// How many hints a single question can give.
+const MAX_HINTS: u32 = 2;
+/// What to type instead of an answer to get a hint with `--type-answers`.
+const HINT_COMMAND: &str = "?";
+
+/// Correct answers in a row before adaptive mode serves harder questions.
+const ADAPT_AFTER: u32 = 3;
+
+/// Correct answers in a row that earn a bonus question.
+const BONUS_STREAK: u32 = 5;
+/// Points for a streak when no harder question is left to make a bonus question of.
+const BONUS_POINTS: u32 = 20;
+
+/// The pause between two rings of the bell, so they are heard as two.
+const BELL_GAP: Duration = Duration::from_millis(200);
+
+/// The most that can be wagered when the bank holds less than this.
+const WAGER_FLOOR: u32 = 10;
+
+/// The tallies of a finished game.
+pub struct GameSummary {
+    pub source: String,
+    pub correct: u32,
+    pub incorrect: u32,
+    pub points: u32,
+    pub hints_used: u32,
+    pub best_streak: u32,
+    /// The players of a hot-seat game, empty when playing alone.
+    pub players: Vec<PlayerScore>,
+    pub duration: Duration,
+    pub results: Vec<QuestionResult>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Correct,
+    Partial,
+    Incorrect,
+    Skipped,
+    TimedOut,
+}
+
+/// How many typos a typed answer may have and still count.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Strictness {
+    /// The answer has to be spelled exactly
+    Exact,
+    /// One typo for every 6 letters, answers shorter than that have to be exact
+    Normal,
+    /// One typo for every 4 letters
+    Lenient,
+}
+
+impl Strictness {
+    fn allowed_typos(self, answer: &str) -> usize {
+        let letters = answer.chars().count();
+        match self {
+            Strictness::Exact => 0,
+            Strictness::Normal => letters / 6,
+            Strictness::Lenient => letters / 4,
+        }
+    }
+}
+
+/// How a single question of the game went.
+pub struct QuestionResult {
+    pub question: Question,
+    pub chosen: Vec<String>,
+    pub outcome: Outcome,
+    pub time: Duration,
+}
+
+pub fn run_game(
+    mut questions: Vec<Question>,
+    source: &str,
+    args: &Args,
+    config: &Config,
+    input: &impl InputSource,
+) -> GameSummary {
+    let mut rng = game_rng(args);
+    let questions = pick_questions(questions, args, &mut rng);
+    let mut state = GameState::new(source, questions);
+    if let Some(count) = args.hot_seat {
+        state.players = read_player_names(input, count, args.batch);
+    }
+    play(state, None, &mut rng, args, config, input)
+}
+
+/// Plays the questions of a source as they come, until it runs dry, the limit is reached or
+/// the player quits.
+pub fn run_endless(
+    more: &mut dyn QuestionSource,
+    source: &str,
+    args: &Args,
+    config: &Config,
+    input: &impl InputSource,
+) -> GameSummary {
+    let mut state = GameState::new(source, Vec::new());
+    if let Some(count) = args.hot_seat {
+        state.players = read_player_names(input, count, args.batch);
+    }
+    play(state, Some(more), &mut game_rng(args), args, config, input)
+}
+
+/// Goes through the questions showing each one with its answer, without asking or scoring.
+pub fn run_review(
+    questions: Vec<Question>,
+    args: &Args,
+    config: &Config,
+    input: &impl InputSource,
+) {
+    let questions = pick_questions(questions, args, &mut game_rng(args));
+    let plain = args.no_color;
+    let count = questions.len();
+    for (number, q) in questions.iter().enumerate() {
+        let width = terminal_width();
+        print_prompt(q, width, plain);
+        if let Some(category) = &q.category {
+            println!("{}", fill(Msg::Category, &[category]));
+        }
+        let answer = q.correct_answers().join(", ");
+        println!(
+            "{}",
+            fill(
+                Msg::Answer,
+                &[&styled_or_plain(&answer, Color::Green, plain)]
+            )
+        );
+        if let Some(explanation) = &q.explanation {
+            println!("{}", wrap(explanation.trim(), width, 0));
+        }
+        println!();
+        if number + 1 < count {
+            println!("{}", text(Msg::NextQuestion));
+            match read_key(input, args.batch) {
+                None => break,
+                Some(code) if config.keybindings.action(code) == Some(Action::Quit) => break,
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+/// Puts the questions to play in order and leaves out the ones that can't be played.
+fn pick_questions(
+    mut questions: Vec<Question>,
+    args: &Args,
+    rng: &mut ChaCha8Rng,
+) -> Vec<Question> {
+    //A question with a single option can't be got wrong, so it isn't a question
+    questions.retain(|q| {
+        let playable = q.option_count() >= 2;
+        if !playable {
+            println!("{}", fill(Msg::SkippingUnplayable, &[&q.label()]));
+        }
+        playable
+    });
+    if args.prioritize_weak {
+        prioritize_weak(&mut questions, &load_mastery(), rng);
+    } else if !args.no_shuffle_questions {
+        questions.shuffle(rng);
+    }
+    if args.spaced_repetition {
+        sort_by_due(&mut questions);
+    }
+    if let Some(limit) = args.limit {
+        questions.truncate(limit);
+    }
+    if questions.is_empty() {
+        println!("{}", text(Msg::NoQuestions));
+        std::process::exit(NO_QUESTIONS_EXIT_CODE);
+    }
+    questions
+}
+
+/// Shuffles the questions so that the less mastered ones tend to come first.
+///
+/// Each question draws a random key weighted by how badly it is known, the highest keys go first.
+fn prioritize_weak(
+    questions: &mut [Question],
+    mastery: &HashMap<String, f64>,
+    rng: &mut ChaCha8Rng,
+) {
+    let mut keyed: Vec<(f64, Question)> = questions
+        .iter()
+        .map(|q| {
+            let weight = 1.0 - mastery.get(&q.stats_key()).copied().unwrap_or(0.0) + 0.05;
+            (rng.gen::<f64>().powf(1.0 / weight), q.clone())
+        })
+        .collect();
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+    for (slot, (_, question)) in questions.iter_mut().zip(keyed) {
+        *slot = question;
+    }
+}
+
+/// A random number generator for the options of one question, seeded from the game seed and
+/// the question so it comes out the same no matter when the question is asked.
+fn question_rng(seed: u64, question: &Question) -> ChaCha8Rng {
+    //FNV-1a, since the std hashers may change between Rust versions
+    let hash = question
+        .stats_key()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    ChaCha8Rng::seed_from_u64(seed ^ hash)
+}
+
+/// Picks a saved game back up at the question it was left on.
+pub fn resume_game(
+    state: GameState,
+    args: &Args,
+    config: &Config,
+    input: &impl InputSource,
+) -> GameSummary {
+    play(state, None, &mut game_rng(args), args, config, input)
+}
+
+fn game_rng(args: &Args) -> ChaCha8Rng {
+    match args.seed {
+        Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+        None => ChaCha8Rng::from_entropy(),
+    }
+}
+
+/// Asks the questions of the game, taking more from `more` whenever they run out.
+fn play(
+    mut state: GameState,
+    mut more: Option<&mut dyn QuestionSource>,
+    rng: &mut ChaCha8Rng,
+    args: &Args,
+    config: &Config,
+    input: &impl InputSource,
+) -> GameSummary {
+    let started = Instant::now();
+    let keys = &config.keybindings;
+    let mastery = if args.verbose {
+        load_mastery()
+    } else {
+        HashMap::new()
+    };
+    let mut results: Vec<QuestionResult> = Vec::new();
+    let plain = args.no_color;
+    let sound = args.sound && !args.batch;
+    let attempts = args.attempts.unwrap_or(1).max(1);
+
+    'questions: while state.index < state.questions.len()
+        || pull_question(&mut state, more.as_deref_mut(), args.limit)
+    {
+        if terminate_requested() {
+            save_and_exit(&state);
+        }
+        //The bonus question is already lined up
+        if args.adaptive && !state.bonus {
+            pick_adaptive(&mut state);
+        }
+        if args.clear_screen && !args.batch {
+            clear_screen();
+            if let Some(previous) = results.last() {
+                print_previous(previous, plain);
+            }
+        }
+        let q = state.questions[state.index].clone();
+        //Remember where we are in case the game gets interrupted during this question
+        track_game(&state);
+        state.index += 1;
+        //In a hot-seat game the players take turns, one question each
+        let player = (!state.players.is_empty()).then(|| (state.index - 1) % state.players.len());
+        if let Some(player) = player {
+            let name = styled_or_plain(&state.players[player].name, Color::Cyan, plain);
+            println!("{}", fill(Msg::YourTurn, &[&name]));
+        }
+
+        //Ask for the wager before the options are revealed
+        let wager = if args.wager {
+            let bank = state.bank.to_string();
+            println!(
+                "{}",
+                fill(
+                    Msg::YourBank,
+                    &[&styled_or_plain(&bank, Color::Yellow, plain)]
+                )
+            );
+            Some(read_wager(input, state.bank, args.batch))
+        } else {
+            None
+        };
+
+        let bonus = std::mem::take(&mut state.bonus);
+        if bonus {
+            println!("{}", fill(Msg::BonusDouble, &[&bonus_banner(plain)]));
+        }
+        let width = terminal_width();
+        print_prompt(&q, width, plain);
+        if args.verbose {
+            if let Some(id) = &q.id {
+                println!("{}", fill(Msg::QuestionId, &[id]));
+            }
+            match mastery.get(&q.stats_key()) {
+                Some(score) => {
+                    let score = format!("{:.0}", score * 100.0);
+                    println!("{}", fill(Msg::Mastery, &[&score]));
+                }
+                None => println!("{}", text(Msg::NotAnsweredBefore)),
+            }
+        }
+
+        //With stable options the layout of a question only depends on the seed and the question
+        let mut stable_rng = args
+            .stable_options
+            .then(|| question_rng(args.seed.unwrap_or_default(), &q));
+        let option_rng = match &mut stable_rng {
+            Some(stable_rng) => stable_rng,
+            None => &mut *rng,
+        };
+
+        //Shuffle the order of the answers and display them
+        let mut wrong_answers: Vec<&str> = q.wrong_answers.iter().map(String::as_str).collect();
+        if let Some(max) = args.max_wrong_answers {
+            if wrong_answers.len() > max {
+                let mut picked = sample(option_rng, wrong_answers.len(), max).into_vec();
+                picked.sort_unstable();
+                wrong_answers = picked.into_iter().map(|i| wrong_answers[i]).collect();
+            }
+        }
+        let mut choices: Vec<(&str, bool)> = q
+            .correct_answers()
+            .into_iter()
+            .map(|answer| (answer, true))
+            .chain(wrong_answers.into_iter().map(|answer| (answer, false)))
+            .collect();
+        let duplicates = dedupe_choices(&mut choices);
+        if !duplicates.is_empty() {
+            let warning = if duplicates.len() == 1 {
+                Msg::DuplicateOption
+            } else {
+                Msg::DuplicateOptions
+            };
+            println!("{}", fill(warning, &[&q.label(), &duplicates.join(", ")]));
+        }
+        if !args.no_shuffle && q.shuffle {
+            choices.shuffle(option_rng);
+        }
+        if !args.type_answers {
+            for (index, (text, _)) in choices.iter().enumerate() {
+                let label = keys.answer_label(index);
+                let indent = label.chars().count() + 2;
+                println!("{}", wrap(&format!("{label}: {text}"), width, indent));
+            }
+        }
+        let solution = q.correct_answers().join(", ");
+        let multi_select = q.is_multi_select();
+        if multi_select && !args.type_answers {
+            println!("{}", text(Msg::SelectAll));
+        }
+
+        let asked = Instant::now();
+        //Lines are read as they come, so there is nothing to time in batch mode or when typing
+        let mut deadline = args
+            .timer
+            .filter(|_| !args.batch && !args.type_answers)
+            .map(|secs| {
+                println!("{}", fill(Msg::YouHaveSeconds, &[&secs]));
+                asked + Duration::from_secs(secs)
+            });
+        //Time spent paused doesn't count towards the answer time
+        let mut paused = Duration::ZERO;
+        let mut selected: Vec<usize> = Vec::new();
+        let mut typed: Option<String> = None;
+        let mut hints = 0;
+        //Read the users response, giving another try on a wrong answer while any are left
+        let all_right = |selected: &[usize]| {
+            selected.iter().all(|s| choices[*s].1)
+                && selected.len() == choices.iter().filter(|(_, correct)| *correct).count()
+        };
+        let mut tries = 1;
+        //With confirmation a picked option waits for Enter before it counts
+        let confirm = args.confirm_answers && !multi_select && !args.batch;
+        loop {
+            let mut pending: Option<usize> = None;
+            let mut last_was_pick = false;
+            loop {
+                if args.type_answers {
+                    //A typed answer picks the option it spells out, if any
+                    println!("{}", text(Msg::TypeAnswer));
+                    let Some(line) = read_line(input, args.batch) else {
+                        break 'questions;
+                    };
+                    if line.trim() == HINT_COMMAND {
+                        give_hint(&mut state, &mut hints, &q.answer, config.hint_penalty);
+                        continue;
+                    }
+                    if let Some((option, exact)) = match_typed(&line, &choices, &q, args.strictness)
+                    {
+                        if !exact && choices[option].1 {
+                            println!(
+                                "{} {}",
+                                styled_or_plain(text(Msg::CloseEnough), Color::Green, plain),
+                                fill(Msg::SpelledAs, &[&choices[option].0])
+                            );
+                        }
+                        selected.push(option);
+                    }
+                    typed = Some(line.trim().to_string());
+                    break;
+                }
+                let code = match read_key_before(input, deadline, args.batch) {
+                    TimedKey::Key(code) => code,
+                    TimedKey::TimedOut => {
+                        println!(
+                            "{} {}",
+                            styled_or_plain(text(Msg::TimesUp), Color::Red, plain),
+                            fill(Msg::CorrectAnswerIs, &[&solution])
+                        );
+                        if sound {
+                            ring(Outcome::TimedOut);
+                        }
+                        state.incorrect += 1;
+                        credit_player(&mut state, player, Outcome::TimedOut, 0);
+                        state.streak = 0;
+                        if args.adaptive {
+                            adapt_level(&mut state, Outcome::TimedOut);
+                        }
+                        if let Some(wager) = wager {
+                            state.bank = state.bank.saturating_sub(wager);
+                        }
+                        println!();
+                        results.push(QuestionResult {
+                            question: q,
+                            chosen: Vec::new(),
+                            outcome: Outcome::TimedOut,
+                            time: asked.elapsed().saturating_sub(paused),
+                        });
+                        if args.survival {
+                            break 'questions;
+                        }
+                        continue 'questions;
+                    }
+                    //Running out of piped answers ends the game like quitting does
+                    TimedKey::Closed => break 'questions,
+                    TimedKey::Terminated => {
+                        println!();
+                        state.index -= 1;
+                        save_and_exit(&state);
+                    }
+                };
+                if multi_select && code == KeyCode::Enter && !selected.is_empty() {
+                    break;
+                }
+                if let (Some(option), KeyCode::Enter) = (pending, code) {
+                    selected.push(option);
+                    break;
+                }
+                let redraw = std::mem::take(&mut last_was_pick);
+                match keys.action(code) {
+                    Some(Action::Answer(option)) if option < choices.len() && confirm => {
+                        pending = Some(option);
+                        last_was_pick = true;
+                        show_pending(
+                            &keys.answer_label(option),
+                            choices[option].0,
+                            redraw && !args.plain,
+                            plain,
+                        );
+                    }
+                    Some(Action::Answer(option)) if option < choices.len() => {
+                        if !multi_select {
+                            selected.push(option);
+                            break;
+                        }
+                        //Pressing an option again deselects it
+                        match selected.iter().position(|s| *s == option) {
+                            Some(position) => {
+                                selected.remove(position);
+                            }
+                            None => selected.push(option),
+                        }
+                        let labels: Vec<String> =
+                            selected.iter().map(|s| keys.answer_label(*s)).collect();
+                        println!("{}", fill(Msg::Selected, &[&labels.join(", ")]));
+                    }
+                    Some(Action::Skip) => {
+                        println!("{}", fill(Msg::Skipped, &[&solution]));
+                        state.skipped += 1;
+                        println!();
+                        results.push(QuestionResult {
+                            question: q,
+                            chosen: Vec::new(),
+                            outcome: Outcome::Skipped,
+                            time: asked.elapsed().saturating_sub(paused),
+                        });
+                        continue 'questions;
+                    }
+                    Some(Action::Hint) => {
+                        give_hint(&mut state, &mut hints, &q.answer, config.hint_penalty)
+                    }
+                    //Timed questions stop the clock instead, Ctrl-C still saves the game
+                    Some(Action::Pause) if deadline.is_some() && !args.batch => {
+                        let pause_started = Instant::now();
+                        println!("{}", text(Msg::Paused));
+                        read_key(input, args.batch);
+                        let pause = pause_started.elapsed();
+                        paused += pause;
+                        deadline = deadline.map(|deadline| deadline + pause);
+                        if let Some(deadline) = deadline {
+                            let remaining = deadline.saturating_duration_since(Instant::now());
+                            println!("{}", fill(Msg::Resumed, &[&remaining.as_secs()]));
+                        }
+                    }
+                    Some(Action::Pause) => {
+                        //Ask this question again when the game is resumed
+                        state.index -= 1;
+                        save_and_exit(&state);
+                    }
+                    Some(Action::Quit) => break 'questions,
+                    _ => {}
+                }
+            }
+            if tries < attempts && !all_right(&selected) {
+                println!(
+                    "{} {}",
+                    styled_or_plain(text(Msg::TryAgain), Color::Yellow, plain),
+                    fill(Msg::TriesLeft, &[&(attempts - tries)])
+                );
+                tries += 1;
+                selected.clear();
+                continue;
+            }
+            break;
+        }
+
+        let time = asked.elapsed().saturating_sub(paused);
+        let chosen: Vec<String> = match typed {
+            Some(typed) => vec![typed],
+            None => selected.iter().map(|s| choices[*s].0.to_string()).collect(),
+        };
+
+        //Show if they got it right or not
+        let picked_correct = selected.iter().filter(|s| choices[**s].1).count();
+        let picked_wrong = selected.len() - picked_correct;
+        let total_correct = choices.iter().filter(|(_, correct)| *correct).count();
+        let outcome = if picked_wrong == 0 && picked_correct == total_correct {
+            println!(
+                "{}",
+                styled_or_plain(text(Msg::Correct), Color::Green, plain)
+            );
+            state.correct += 1;
+            state.streak += 1;
+            state.best_streak = state.best_streak.max(state.streak);
+            //Each extra try takes an equal share off the points
+            let earned = (POINTS_PER_QUESTION * (attempts - tries + 1) / attempts)
+                .saturating_sub(hints * config.hint_penalty)
+                * q.difficulty.map_or(1, Difficulty::weight)
+                * if bonus { 2 } else { 1 };
+            state.points += earned;
+            credit_player(&mut state, player, Outcome::Correct, earned);
+            if args.bonus_rounds && state.streak % BONUS_STREAK == 0 {
+                award_bonus(&mut state, player, &q, plain);
+            }
+            if let Some(wager) = wager {
+                state.bank += wager;
+            }
+            Outcome::Correct
+        } else if args.partial_credit && picked_wrong == 0 && picked_correct > 0 {
+            println!(
+                "{} {}",
+                styled_or_plain(text(Msg::PartiallyCorrect), Color::Yellow, plain),
+                fill(
+                    Msg::FoundSome,
+                    &[&picked_correct, &total_correct, &solution]
+                )
+            );
+            state.partial += 1;
+            state.streak = 0;
+            Outcome::Partial
+        } else {
+            println!(
+                "{} {}",
+                styled_or_plain(text(Msg::Wrong), Color::Red, plain),
+                fill(Msg::CorrectAnswerIs, &[&solution])
+            );
+            state.incorrect += 1;
+            credit_player(&mut state, player, Outcome::Incorrect, 0);
+            state.streak = 0;
+            if let Some(wager) = wager {
+                state.bank = state.bank.saturating_sub(wager);
+            }
+            Outcome::Incorrect
+        };
+        if sound {
+            ring(outcome);
+        }
+        if let Some(explanation) = &q.explanation {
+            println!("{}", wrap(explanation.trim(), width, 0));
+        }
+        if args.adaptive {
+            adapt_level(&mut state, outcome);
+        }
+        if let Some(source) = &q.source {
+            if !args.batch {
+                offer_source(input, source);
+            }
+        }
+        if !args.no_live_score {
+            println!(
+                "{}",
+                fill(
+                    Msg::LiveScore,
+                    &[&state.correct, &state.incorrect, &state.points]
+                )
+            );
+        }
+        println!();
+        results.push(QuestionResult {
+            question: q,
+            chosen,
+            outcome,
+            time,
+        });
+        //In survival mode the first miss ends the run
+        if args.survival && outcome != Outcome::Correct {
+            break;
+        }
+    }
+
+    if args.survival {
+        let survived = state.correct.to_string();
+        println!(
+            "{}",
+            fill(
+                Msg::Survived,
+                &[&styled_or_plain(&survived, Color::Yellow, plain)]
+            )
+        );
+    }
+
+    println!(
+        "{}",
+        fill(
+            Msg::Summary,
+            &[
+                &styled_or_plain(&state.correct.to_string(), Color::Green, plain),
+                &styled_or_plain(&state.incorrect.to_string(), Color::Red, plain)
+            ]
+        )
+    );
+    if let Some(percentage) = percentage(
+        state.correct,
+        state.correct + state.incorrect + state.partial,
+    ) {
+        println!(
+            "{}",
+            fill(Msg::PercentCorrect, &[&format!("{percentage:.0}")])
+        );
+    }
+    let weighted = state.questions.iter().any(|q| q.difficulty.is_some());
+    let scored = if weighted {
+        Msg::ScoredWeighted
+    } else {
+        Msg::Scored
+    };
+    println!(
+        "{}",
+        fill(
+            scored,
+            &[&styled_or_plain(
+                &state.points.to_string(),
+                Color::Yellow,
+                plain
+            )]
+        )
+    );
+    if state.hints_used > 0 {
+        println!("{}", fill(Msg::HintsUsed, &[&state.hints_used]));
+    }
+    if state.partial > 0 {
+        println!("{}", fill(Msg::PartiallyRight, &[&state.partial]));
+    }
+    if state.skipped > 0 {
+        println!("{}", fill(Msg::SkippedCount, &[&state.skipped]));
+    }
+    if args.wager {
+        let bank = state.bank.to_string();
+        println!(
+            "{}",
+            fill(
+                Msg::FinalBank,
+                &[&styled_or_plain(&bank, Color::Yellow, plain)]
+            )
+        );
+    }
+    print_timing(&results);
+    if !state.players.is_empty() {
+        print_ranking(&state.players, plain);
+    }
+    untrack_game();
+    delete_saved_game();
+
+    GameSummary {
+        source: state.source,
+        correct: state.correct,
+        incorrect: state.incorrect,
+        points: state.points,
+        hints_used: state.hints_used,
+        best_streak: state.best_streak,
+        players: state.players,
+        duration: started.elapsed(),
+        results,
+    }
+}
+
+/// The share of the answers that were correct, or `None` if nothing was answered.
+fn percentage(correct: u32, answered: u32) -> Option<f64> {
+    (answered > 0).then(|| f64::from(correct) * 100.0 / f64::from(answered))
+}
+
+/// Asks for the names of the players of a hot-seat game.
+fn read_player_names(input: &impl InputSource, count: u8, batch: bool) -> Vec<PlayerScore> {
+    let mut players = Vec::new();
+    for number in 1..=count {
+        println!("{}", fill(Msg::PlayerName, &[&number]));
+        let name = match read_line(input, batch) {
+            Some(line) if !line.trim().is_empty() => line.trim().to_string(),
+            _ => fill(Msg::DefaultPlayer, &[&number]),
+        };
+        players.push(PlayerScore::new(name));
+    }
+    players
+}
+
+/// Adds the answer to the tallies of the player whose turn it was, if anyone's.
+fn credit_player(state: &mut GameState, player: Option<usize>, outcome: Outcome, points: u32) {
+    let Some(player) = player else {
+        return;
+    };
+    let score = &mut state.players[player];
+    match outcome {
+        Outcome::Correct => {
+            score.correct += 1;
+            score.points += points;
+        }
+        Outcome::Incorrect | Outcome::TimedOut => score.incorrect += 1,
+        Outcome::Partial | Outcome::Skipped => {}
+    }
+}
+
+/// Lists the players of a hot-seat game from most to fewest points.
+fn print_ranking(players: &[PlayerScore], plain: bool) {
+    let mut ranking: Vec<&PlayerScore> = players.iter().collect();
+    ranking.sort_by(|a, b| b.points.cmp(&a.points).then(b.correct.cmp(&a.correct)));
+    println!("{}", text(Msg::Ranking));
+    for (place, player) in ranking.iter().enumerate() {
+        println!(
+            "{}",
+            fill(
+                Msg::RankingLine,
+                &[
+                    &(place + 1),
+                    &styled_or_plain(&player.name, Color::Cyan, plain),
+                    &player.points,
+                    &player.correct,
+                    &player.incorrect
+                ]
+            )
+        );
+    }
+}
+
+/// Saves the game so it can be resumed and ends the program.
+fn save_and_exit(state: &GameState) -> ! {
+    match save_game(state) {
+        Ok(()) => println!("{}", text(Msg::GameSaved)),
+        Err(err) => println!("{}", fill(Msg::CouldNotSave, &[&err])),
+    }
+    std::process::exit(0);
+}
+
+/// Rings the terminal bell once for a correct answer and twice for anything else.
+fn ring(outcome: Outcome) {
+    let times = if outcome == Outcome::Correct { 1 } else { 2 };
+    for i in 0..times {
+        if i > 0 {
+            thread::sleep(BELL_GAP);
+        }
+        print!("\x07");
+        let _ = stdout().flush();
+    }
+}
+
+fn clear_screen() {
+    let _ = execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0));
+}
+
+/// Recaps the last question at the top of a freshly cleared screen.
+fn print_previous(result: &QuestionResult, plain: bool) {
+    let verdict = match result.outcome {
+        Outcome::Correct => styled_or_plain(text(Msg::Correct), Color::Green, plain),
+        Outcome::Partial => styled_or_plain(text(Msg::PartiallyCorrect), Color::Yellow, plain),
+        Outcome::Incorrect => styled_or_plain(text(Msg::Wrong), Color::Red, plain),
+        Outcome::Skipped => styled_or_plain(text(Msg::SkippedShort), Color::Yellow, plain),
+        Outcome::TimedOut => styled_or_plain(text(Msg::TimesUp), Color::Red, plain),
+    };
+    println!(
+        "{}",
+        fill(
+            Msg::PreviousQuestion,
+            &[
+                &result.question.text,
+                &verdict,
+                &result.question.correct_answers().join(", ")
+            ]
+        )
+    );
+    println!();
+}
+
+/// Moves the remaining question closest to the targeted difficulty up to be asked next.
+///
+/// Questions without a difficulty count as medium.
+fn pick_adaptive(state: &mut GameState) {
+    let remaining = &mut state.questions[state.index..];
+    let level = state.level;
+    let closest = remaining
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, q)| {
+            let question_level = q.difficulty.map_or(1, |d| d.level());
+            question_level.abs_diff(level)
+        })
+        .map(|(i, _)| i);
+    if let Some(closest) = closest {
+        remaining.swap(0, closest);
+    }
+}
+
+/// Shows the option waiting to be locked in, replacing the line of the previous one.
+fn show_pending(label: &str, text: &str, redraw: bool, plain: bool) {
+    if redraw {
+        let _ = execute!(
+            stdout(),
+            MoveToPreviousLine(1),
+            Clear(ClearType::CurrentLine)
+        );
+    }
+    let option = format!("{label}: {text}");
+    let option = emphasized_or_plain(&option, Color::Cyan, Some(Attribute::Reverse), plain);
+    println!("{}", fill(Msg::PendingConfirm, &[&option]));
+}
+
+/// Adds the next question of the source to the game, unless there is none or the game
+/// already has as many as the limit allows.
+fn pull_question(
+    state: &mut GameState,
+    more: Option<&mut dyn QuestionSource>,
+    limit: Option<usize>,
+) -> bool {
+    let Some(more) = more else {
+        return false;
+    };
+    if limit.is_some_and(|limit| state.questions.len() >= limit) {
+        return false;
+    }
+    match more.next_question() {
+        Some(question) => {
+            state.questions.push(question);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Rewards a streak by lining up a harder question as the bonus question, or with flat points
+/// when none is left. In a hot-seat game the next question belongs to someone else, so it is
+/// always the points.
+fn award_bonus(state: &mut GameState, player: Option<usize>, answered: &Question, plain: bool) {
+    let level = answered.difficulty.map_or(1, Difficulty::level);
+    let remaining = &mut state.questions[state.index..];
+    let harder = remaining
+        .iter()
+        .position(|q| q.difficulty.map_or(1, Difficulty::level) > level);
+    match harder {
+        Some(harder) if player.is_none() => {
+            remaining[..=harder].rotate_right(1);
+            state.bonus = true;
+            println!(
+                "{}",
+                fill(Msg::BonusHarder, &[&bonus_banner(plain), &state.streak])
+            );
+        }
+        _ => {
+            state.points += BONUS_POINTS;
+            if let Some(player) = player {
+                state.players[player].points += BONUS_POINTS;
+            }
+            println!(
+                "{}",
+                fill(
+                    Msg::BonusPoints,
+                    &[&bonus_banner(plain), &state.streak, &BONUS_POINTS]
+                )
+            );
+        }
+    }
+}
+
+fn bonus_banner(plain: bool) -> impl Display {
+    emphasized_or_plain("BONUS!", Color::Magenta, Some(Attribute::Bold), plain)
+}
+
+/// Steps the targeted difficulty up after a run of correct answers and down after a miss.
+fn adapt_level(state: &mut GameState, outcome: Outcome) {
+    match outcome {
+        Outcome::Correct if state.streak % ADAPT_AFTER == 0 => {
+            state.level = (state.level + 1).min(2);
+        }
+        Outcome::Incorrect | Outcome::Partial | Outcome::TimedOut => {
+            state.level = state.level.saturating_sub(1);
+        }
+        _ => {}
+    }
+}
+
+/// Prints the average answer time along with the fastest and slowest question.
+fn print_timing(results: &[QuestionResult]) {
+    let answered: Vec<&QuestionResult> = results
+        .iter()
+        .filter(|r| !matches!(r.outcome, Outcome::Skipped | Outcome::TimedOut))
+        .collect();
+    let (Some(fastest), Some(slowest)) = (
+        answered.iter().min_by_key(|r| r.time),
+        answered.iter().max_by_key(|r| r.time),
+    ) else {
+        return;
+    };
+    let total: Duration = answered.iter().map(|r| r.time).sum();
+    let average = total / answered.len() as u32;
+    let seconds = |time: Duration| format!("{:.1}", time.as_secs_f64());
+    println!("{}", fill(Msg::AverageTime, &[&seconds(average)]));
+    println!(
+        "{}",
+        fill(
+            Msg::Fastest,
+            &[&seconds(fastest.time), &fastest.question.text]
+        )
+    );
+    println!(
+        "{}",
+        fill(
+            Msg::Slowest,
+            &[&seconds(slowest.time), &slowest.question.text]
+        )
+    );
+}
+
+/// The width of the terminal, or 80 columns if it can't be found out.
+fn terminal_width() -> usize {
+    match crossterm::terminal::size() {
+        Ok((columns, _)) if columns > 0 => columns as usize,
+        _ => 80,
+    }
+}
+
+/// Breaks the text into lines of at most `width` characters on word boundaries.
+///
+/// Lines after the first are indented by `indent` spaces. Words longer than a line are left whole.
+fn wrap(text: &str, width: usize, indent: usize) -> String {
+    let mut wrapped = String::new();
+    let mut line_length = 0;
+    for (i, word) in text.split(' ').enumerate() {
+        let word_length = word.chars().count();
+        if i > 0 {
+            if line_length > indent && line_length + 1 + word_length > width {
+                wrapped.push('\n');
+                wrapped.push_str(&" ".repeat(indent));
+                line_length = indent;
+            } else {
+                wrapped.push(' ');
+                line_length += 1;
+            }
+        }
+        wrapped.push_str(word);
+        line_length += word_length;
+    }
+    wrapped
+}
+
+/// Drops options whose text another option already has, and returns the texts that were doubled.
+///
+/// An option stays correct if any of its copies was a correct answer.
+fn dedupe_choices(choices: &mut Vec<(&str, bool)>) -> Vec<String> {
+    let mut kept: Vec<(&str, bool)> = Vec::with_capacity(choices.len());
+    let mut duplicates = Vec::new();
+    for (text, correct) in choices.drain(..) {
+        match kept
+            .iter_mut()
+            .find(|(kept_text, _)| kept_text.trim() == text.trim())
+        {
+            Some(existing) => {
+                existing.1 |= correct;
+                if !duplicates.contains(&text.trim().to_string()) {
+                    duplicates.push(text.trim().to_string());
+                }
+            }
+            None => kept.push((text, correct)),
+        }
+    }
+    *choices = kept;
+    duplicates
+}
+
+/// Finds the option a typed answer means, the one it spells out or else the closest one within
+/// the typos the strictness allows. Also tells whether it was spelled out exactly.
+///
+/// The acceptable answers of the question stand in for its main answer.
+fn match_typed(
+    typed: &str,
+    choices: &[(&str, bool)],
+    question: &Question,
+    strictness: Strictness,
+) -> Option<(usize, bool)> {
+    let typed = normalize_answer(typed);
+    let answer = choices
+        .iter()
+        .position(|(text, correct)| *correct && *text == question.answer);
+    let alternatives = answer.into_iter().flat_map(|answer| {
+        question
+            .acceptable_answers
+            .iter()
+            .map(move |text| (answer, text.as_str()))
+    });
+    choices
+        .iter()
+        .map(|(text, _)| *text)
+        .enumerate()
+        .chain(alternatives)
+        .map(|(option, text)| {
+            let text = normalize_answer(text);
+            let distance = levenshtein(&typed, &text);
+            (option, distance, strictness.allowed_typos(&text))
+        })
+        .filter(|(_, distance, allowed)| distance <= allowed)
+        .min_by_key(|(_, distance, _)| *distance)
+        .map(|(option, distance, _)| (option, distance == 0))
+}
+
+/// Prints the prompt, with a badge for the difficulty if it has one.
+fn print_prompt(q: &Question, width: usize, plain: bool) {
+    let prompt = wrap(&format!(" === {} ===", q.text), width, 5);
+    match q.difficulty {
+        Some(difficulty) => {
+            let badge = format!("[{difficulty}]");
+            println!(
+                "{prompt} {}",
+                styled_or_plain(&badge, difficulty_color(difficulty), plain)
+            );
+        }
+        None => println!("{prompt}"),
+    }
+}
+
+fn difficulty_color(difficulty: Difficulty) -> Color {
+    match difficulty {
+        Difficulty::Easy => Color::Green,
+        Difficulty::Medium => Color::Yellow,
+        Difficulty::Hard => Color::Red,
+    }
+}
+
+/// Shows the next hint for the answer while the question has any left, counting it against
+/// the points.
+fn give_hint(state: &mut GameState, hints: &mut u32, answer: &str, penalty: u32) {
+    if *hints >= MAX_HINTS {
+        println!("{}", text(Msg::NoMoreHints));
+        return;
+    }
+    *hints += 1;
+    state.hints_used += 1;
+    println!("{}", fill(Msg::Hint, &[&penalty, &hint(answer, *hints)]));
+}
+
+/// Reveals the first letter of the answer, or its first word on the second hint.
+fn hint(answer: &str, level: u32) -> String {
+    let revealed = if level == 1 {
+        answer.chars().take(1).collect::<String>()
+    } else {
+        answer.split_whitespace().next().unwrap_or("").to_string()
+    };
+    format!("{revealed}...")
+}
+
+fn offer_source(input: &impl InputSource, url: &str) {
+    println!("{}", fill(Msg::ReadMore, &[&url]));
+    if read_key(input, false) == Some(KeyCode::Char('o')) {
+        if let Err(err) = open::that(url) {
+            println!("{}", fill(Msg::CouldNotOpen, &[&url, &err]));
+        }
+    }
+}
+
+fn read_wager(input: &impl InputSource, bank: u32, batch: bool) -> u32 {
+    let max = bank.max(WAGER_FLOOR);
+    loop {
+        println!("{}", fill(Msg::Wager, &[&max]));
+        let Some(line) = read_line(input, batch) else {
+            return 0;
+        };
+        match line.trim().parse::<u32>() {
+            Ok(wager) if wager <= max => return wager,
+            _ => println!("{}", fill(Msg::WagerRange, &[&max])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::input::MockInput;
+    use crate::save::set_test_save_dir;
+
+    fn question(text: &str) -> Question {
+        Question {
+            text: text.to_string(),
+            answer: "4".to_string(),
+            wrong_answers: vec!["5".to_string(), "6".to_string()],
+            ..Question::new()
+        }
+    }
+
+    #[test]
+    fn duplicate_options_are_shown_once_and_stay_correct() {
+        let mut choices = vec![("4", false), ("5", false), ("4", true), ("5 ", false)];
+        let duplicates = dedupe_choices(&mut choices);
+        assert_eq!(choices, [("4", true), ("5", false)]);
+        assert_eq!(duplicates, ["4", "5"]);
+    }
+
+    #[test]
+    fn plain_output_has_no_escape_codes() {
+        assert_eq!(bonus_banner(true).to_string(), "BONUS!");
+        assert_eq!(
+            styled_or_plain("Correct!", Color::Green, true).to_string(),
+            "Correct!"
+        );
+        assert!(bonus_banner(false).to_string().contains('\x1b'));
+    }
+
+    /// A directory the game is saved in for the rest of the test, instead of the profile.
+    fn temp_root() -> TempDir {
+        let root = TempDir::new().unwrap();
+        set_test_save_dir(root.path());
+        root
+    }
+
+    /// Plays two questions with the correct answer always on the first key.
+    fn play_keys(keys: &str, flags: &[&str]) -> GameSummary {
+        play_input(MockInput::keys(keys), flags)
+    }
+
+    fn play_input(input: MockInput, flags: &[&str]) -> GameSummary {
+        let _root = temp_root();
+        let args = Args::parse_from(
+            ["the_quiz", "--no-shuffle", "--no-shuffle-questions"]
+                .iter()
+                .chain(flags),
+        );
+        let questions = vec![question("What is 2 + 2?"), question("What is 1 + 3?")];
+        run_game(questions, "test", &args, &Config::default(), &input)
+    }
+
+    #[test]
+    fn a_question_mark_asks_for_a_hint_when_typing() {
+        let summary = play_keys("?\n4\n4\n", &["--type-answers"]);
+        assert_eq!(summary.correct, 2);
+        assert_eq!(summary.hints_used, 1);
+        let penalty = Config::default().hint_penalty;
+        assert_eq!(summary.points, 2 * POINTS_PER_QUESTION - penalty);
+    }
+
+    #[test]
+    fn streaks_line_up_a_bonus_question() {
+        let _root = temp_root();
+        let args = Args::parse_from([
+            "the_quiz",
+            "--no-shuffle",
+            "--no-shuffle-questions",
+            "--bonus-rounds",
+        ]);
+        let mut questions: Vec<Question> = (0..6).map(|i| question(&format!("Q{i}"))).collect();
+        questions.push(Question {
+            difficulty: Some(Difficulty::Hard),
+            ..question("Hard")
+        });
+        let summary = run_game(
+            questions,
+            "test",
+            &args,
+            &Config::default(),
+            &MockInput::keys("1111111"),
+        );
+        assert_eq!(summary.results[5].question.text, "Hard");
+        //Six plain questions and the hard one, worth three times as much and doubled
+        assert_eq!(
+            summary.points,
+            6 * POINTS_PER_QUESTION + 6 * POINTS_PER_QUESTION
+        );
+    }
+
+    #[test]
+    fn streaks_without_harder_questions_earn_flat_points() {
+        let _root = temp_root();
+        let args = Args::parse_from([
+            "the_quiz",
+            "--no-shuffle",
+            "--no-shuffle-questions",
+            "--bonus-rounds",
+        ]);
+        let questions: Vec<Question> = (0..5).map(|i| question(&format!("Q{i}"))).collect();
+        let summary = run_game(
+            questions,
+            "test",
+            &args,
+            &Config::default(),
+            &MockInput::keys("11111"),
+        );
+        assert_eq!(summary.points, 5 * POINTS_PER_QUESTION + BONUS_POINTS);
+    }
+
+    fn outcomes(summary: &GameSummary) -> Vec<Outcome> {
+        summary
+            .results
+            .iter()
+            .map(|result| result.outcome)
+            .collect()
+    }
+
+    #[test]
+    fn correct_answers_score() {
+        let summary = play_keys("11", &[]);
+        assert_eq!(summary.correct, 2);
+        assert_eq!(summary.points, 2 * POINTS_PER_QUESTION);
+        assert_eq!(outcomes(&summary), [Outcome::Correct, Outcome::Correct]);
+    }
+
+    #[test]
+    fn wrong_answers_count_against() {
+        let summary = play_keys("21", &[]);
+        assert_eq!(summary.correct, 1);
+        assert_eq!(summary.incorrect, 1);
+        assert_eq!(outcomes(&summary), [Outcome::Incorrect, Outcome::Correct]);
+    }
+
+    #[test]
+    fn skipped_questions_are_not_answered() {
+        let summary = play_keys("s1", &[]);
+        assert_eq!(summary.correct, 1);
+        assert_eq!(summary.incorrect, 0);
+        assert_eq!(outcomes(&summary), [Outcome::Skipped, Outcome::Correct]);
+    }
+
+    #[test]
+    fn quitting_ends_the_game() {
+        let summary = play_keys("1q", &[]);
+        assert_eq!(outcomes(&summary), [Outcome::Correct]);
+    }
+
+    #[test]
+    fn esc_ends_the_game_with_the_answers_so_far() {
+        let summary = play_keys("1\x1b", &[]);
+        assert_eq!(outcomes(&summary), [Outcome::Correct]);
+        assert_eq!(summary.points, POINTS_PER_QUESTION);
+    }
+
+    #[test]
+    fn confirmation_lets_the_pick_change() {
+        //The wrong pick is replaced before Enter locks in the right one
+        let summary = play_keys("21\n1\n", &["--confirm-answers"]);
+        assert_eq!(outcomes(&summary), [Outcome::Correct, Outcome::Correct]);
+    }
+
+    #[test]
+    fn confirmation_waits_for_enter_on_a_real_terminal() {
+        //Outside of raw mode the wrong pick would be locked in by the Enter that follows it
+        let summary = play_input(MockInput::typed("21\n1\n"), &["--confirm-answers"]);
+        assert_eq!(outcomes(&summary), [Outcome::Correct, Outcome::Correct]);
+    }
+
+    #[test]
+    fn multi_select_takes_several_keys_on_a_real_terminal() {
+        let _root = temp_root();
+        let args = Args::parse_from(["the_quiz", "--no-shuffle", "--no-shuffle-questions"]);
+        let question = Question {
+            text: "Which are prime?".to_string(),
+            answer: "2".to_string(),
+            extra_answers: vec!["3".to_string()],
+            wrong_answers: vec!["4".to_string()],
+            ..Question::new()
+        };
+        let summary = run_game(
+            vec![question],
+            "test",
+            &args,
+            &Config::default(),
+            &MockInput::typed("12\n"),
+        );
+        assert_eq!(outcomes(&summary), [Outcome::Correct]);
+    }
+
+    #[test]
+    fn typos_are_forgiven_in_longer_answers() {
+        let q = Question::new();
+        let choices = [("Canberra", true), ("Sydney", false)];
+        assert_eq!(
+            match_typed("canbera", &choices, &q, Strictness::Normal),
+            Some((0, false))
+        );
+        assert_eq!(
+            match_typed("canbera", &choices, &q, Strictness::Exact),
+            None
+        );
+        assert_eq!(
+            match_typed(" CANBERRA ", &choices, &q, Strictness::Exact),
+            Some((0, true))
+        );
+    }
+
+    #[test]
+    fn short_answers_have_to_be_exact() {
+        let q = Question::new();
+        let choices = [("Cat", true), ("Dog", false)];
+        assert_eq!(match_typed("bat", &choices, &q, Strictness::Normal), None);
+    }
+
+    #[test]
+    fn acceptable_answers_count_as_the_answer() {
+        let q = Question {
+            answer: "United States".to_string(),
+            acceptable_answers: vec!["USA".to_string(), "America".to_string()],
+            ..Question::new()
+        };
+        let choices = [("Canada", false), ("United States", true)];
+        assert_eq!(
+            match_typed("usa", &choices, &q, Strictness::Exact),
+            Some((1, true))
+        );
+        assert_eq!(
+            match_typed("Amerika", &choices, &q, Strictness::Normal),
+            Some((1, false))
+        );
+    }
+
+    #[test]
+    fn running_out_of_time_is_wrong() {
+        let summary = play_keys("", &["--timer", "0"]);
+        assert_eq!(summary.incorrect, 2);
+        assert_eq!(outcomes(&summary), [Outcome::TimedOut, Outcome::TimedOut]);
+    }
+}
@@ -0,0 +1,271 @@
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat};
+
+use crate::dirs::app_dirs;
+use crate::game::GameSummary;
+use crate::question_stats::misses_by_category;
+
+const HISTORY_FILENAME: &str = "history.log";
+/// Marks the games that were a daily challenge, in an optional column after the duration.
+const DAILY_TAG: &str = "daily=true";
+/// Starts the optional column holding the seed of the game.
+const SEED_PREFIX: &str = "seed=";
+/// How many categories and months the `stats` report lists.
+const STATS_ROWS: usize = 5;
+/// The width of a full bar in the accuracy trend.
+const TREND_BAR_WIDTH: usize = 20;
+
+/// One line of the history log.
+pub struct HistoryEntry {
+    timestamp: String,
+    source: String,
+    correct: u32,
+    incorrect: u32,
+    score: u32,
+    duration_secs: u64,
+    daily: bool,
+    seed: Option<u64>,
+}
+
+impl HistoryEntry {
+    pub fn new(summary: &GameSummary, daily: bool, seed: Option<u64>) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+            source: summary.source.clone(),
+            correct: summary.correct,
+            incorrect: summary.incorrect,
+            score: summary.points,
+            duration_secs: summary.duration.as_secs(),
+            daily,
+            seed,
+        }
+    }
+
+    fn to_line(&self) -> String {
+        let mut line = format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.timestamp,
+            self.source,
+            self.correct,
+            self.incorrect,
+            self.score,
+            self.duration_secs
+        );
+        if self.daily {
+            line.push('\t');
+            line.push_str(DAILY_TAG);
+        }
+        if let Some(seed) = self.seed {
+            line.push_str(&format!("\t{SEED_PREFIX}{seed}"));
+        }
+        line
+    }
+
+    fn from_line(line: &str) -> Option<HistoryEntry> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if !(6..=8).contains(&fields.len()) {
+            return None;
+        }
+        let tags = &fields[6..];
+        Some(HistoryEntry {
+            timestamp: fields[0].to_string(),
+            source: fields[1].to_string(),
+            correct: fields[2].parse().ok()?,
+            incorrect: fields[3].parse().ok()?,
+            score: fields[4].parse().ok()?,
+            duration_secs: fields[5].parse().ok()?,
+            daily: tags.contains(&DAILY_TAG),
+            seed: tags
+                .iter()
+                .find_map(|tag| tag.strip_prefix(SEED_PREFIX)?.parse().ok()),
+        })
+    }
+
+    fn date(&self) -> Option<NaiveDate> {
+        DateTime::parse_from_rfc3339(&self.timestamp)
+            .ok()
+            .map(|date| date.date_naive())
+    }
+}
+
+fn history_path() -> std::io::Result<PathBuf> {
+    app_dirs().profile_file(HISTORY_FILENAME)
+}
+
+/// Appends the game to the history log. Failing to do so only warns, the game is over anyway.
+pub fn append_history(entry: &HistoryEntry) {
+    let result = history_path().and_then(|path| {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", entry.to_line())
+    });
+    if let Err(err) = result {
+        println!("Warning: could not write to the history log: {err}");
+    }
+}
+
+fn read_history() -> std::io::Result<Vec<HistoryEntry>> {
+    let contents = fs::read_to_string(history_path()?)?;
+    Ok(contents
+        .lines()
+        .filter_map(HistoryEntry::from_line)
+        .collect())
+}
+
+/// Whether the history shows a daily challenge that was finished on the given date.
+pub fn played_daily_on(date: NaiveDate) -> bool {
+    match read_history() {
+        Ok(entries) => entries
+            .iter()
+            .any(|entry| entry.daily && entry.date() == Some(date)),
+        Err(_) => false,
+    }
+}
+
+/// The tallies of every logged game together.
+#[derive(Default)]
+pub struct CareerStats {
+    pub correct: u32,
+    pub dailies: u32,
+}
+
+/// Adds up the history log. A missing or unreadable log counts as no games played.
+pub fn career_stats() -> CareerStats {
+    let mut stats = CareerStats::default();
+    for entry in read_history().unwrap_or_default() {
+        stats.correct += entry.correct;
+        if entry.daily {
+            stats.dailies += 1;
+        }
+    }
+    stats
+}
+
+/// Prints the logged games, oldest first, optionally only the last few of them.
+pub fn print_history(last: Option<usize>) {
+    let entries = match read_history() {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            println!("No games have been played yet.");
+            return;
+        }
+        Err(err) => {
+            println!("Could not read the history log: {err}");
+            std::process::exit(1);
+        }
+    };
+    if entries.is_empty() {
+        println!("No games have been played yet.");
+        return;
+    }
+    let skip = match last {
+        Some(n) => entries.len().saturating_sub(n),
+        None => 0,
+    };
+
+    println!(
+        "{:<17} {:<6} {:>7} {:>9} {:>6} {:>8}",
+        "Date", "Source", "Correct", "Incorrect", "Score", "Duration"
+    );
+    for entry in &entries[skip..] {
+        let date = match DateTime::parse_from_rfc3339(&entry.timestamp) {
+            Ok(date) => date.format("%Y-%m-%d %H:%M").to_string(),
+            Err(_) => entry.timestamp.clone(),
+        };
+        println!(
+            "{:<17} {:<6} {:>7} {:>9} {:>6} {:>8}{}{}",
+            date,
+            entry.source,
+            entry.correct,
+            entry.incorrect,
+            entry.score,
+            format_duration(entry.duration_secs),
+            if entry.daily { "  daily" } else { "" },
+            entry
+                .seed
+                .map(|seed| format!("  seed {seed}"))
+                .unwrap_or_default()
+        );
+    }
+}
+
+/// Prints the totals of all logged games, the most missed categories and the accuracy of the
+/// last few months.
+pub fn print_stats() {
+    let entries = match read_history() {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+        Err(err) => {
+            println!("Could not read the history log: {err}");
+            std::process::exit(1);
+        }
+    };
+    if entries.is_empty() {
+        println!("No games have been played yet.");
+        return;
+    }
+    let correct: u32 = entries.iter().map(|entry| entry.correct).sum();
+    let answered: u32 = entries
+        .iter()
+        .map(|entry| entry.correct + entry.incorrect)
+        .sum();
+    let played: u64 = entries.iter().map(|entry| entry.duration_secs).sum();
+    println!("{:<20}{}", "Games played:", entries.len());
+    println!("{:<20}{answered}", "Questions answered:");
+    println!("{:<20}{}", "Accuracy:", format_accuracy(correct, answered));
+    println!("{:<20}{}", "Time played:", format_duration(played));
+
+    match misses_by_category() {
+        Ok(misses) if !misses.is_empty() => {
+            println!();
+            println!("Most missed categories:");
+            for (category, incorrect) in misses.iter().take(STATS_ROWS) {
+                println!("{incorrect:>5} wrong  {category}");
+            }
+        }
+        Ok(_) => {}
+        Err(err) => println!("Warning: could not read the question stats: {err}"),
+    }
+
+    //Games are logged in order, so the months come out oldest first
+    let mut months: Vec<(String, u32, u32)> = Vec::new();
+    for entry in &entries {
+        let Some(date) = entry.date() else {
+            continue;
+        };
+        let month = date.format("%Y-%m").to_string();
+        match months.last_mut() {
+            Some((last, correct, answered)) if *last == month => {
+                *correct += entry.correct;
+                *answered += entry.correct + entry.incorrect;
+            }
+            _ => months.push((month, entry.correct, entry.correct + entry.incorrect)),
+        }
+    }
+    println!();
+    println!("Accuracy by month:");
+    for (month, correct, answered) in &months[months.len().saturating_sub(STATS_ROWS)..] {
+        let filled = (*correct as usize * TREND_BAR_WIDTH)
+            .checked_div(*answered as usize)
+            .unwrap_or(0);
+        println!(
+            "{month}  {}{}  {}",
+            "#".repeat(filled),
+            ".".repeat(TREND_BAR_WIDTH - filled),
+            format_accuracy(*correct, *answered)
+        );
+    }
+}
+
+fn format_accuracy(correct: u32, answered: u32) -> String {
+    if answered == 0 {
+        return "-".to_string();
+    }
+    format!("{:.0}%", f64::from(correct) * 100.0 / f64::from(answered))
+}
+
+fn format_duration(secs: u64) -> String {
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
@@ -0,0 +1,274 @@
+#[cfg(test)]
+use std::cell::{Cell, RefCell};
+#[cfg(test)]
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+
+use crate::save::save_tracked_game;
+use crate::signals::terminate_requested;
+
+/// How often waiting for a key checks whether SIGTERM arrived.
+const SIGNAL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where the key presses come from.
+pub trait InputSource {
+    /// Waits up to `timeout` for an event, returning whether there is one to take.
+    fn poll(&self, timeout: Duration) -> crossterm::Result<bool>;
+    /// Takes the next event, waiting for one if there is none yet.
+    fn next_event(&self) -> crossterm::Result<Event>;
+    /// Switches between getting every key as it is pressed and getting whole lines.
+    fn set_raw_mode(&self, raw: bool);
+}
+
+/// Keeps the input in raw mode until it is dropped, so single key presses arrive without
+/// waiting for Enter.
+pub struct RawMode<'a, I: InputSource>(&'a I);
+
+impl<'a, I: InputSource> RawMode<'a, I> {
+    pub fn enter(input: &'a I) -> RawMode<'a, I> {
+        input.set_raw_mode(true);
+        RawMode(input)
+    }
+}
+
+impl<I: InputSource> Drop for RawMode<'_, I> {
+    fn drop(&mut self) {
+        self.0.set_raw_mode(false);
+    }
+}
+
+/// The terminal the quiz runs in.
+pub struct CrosstermInput;
+
+impl InputSource for CrosstermInput {
+    fn poll(&self, timeout: Duration) -> crossterm::Result<bool> {
+        poll(timeout)
+    }
+
+    fn next_event(&self) -> crossterm::Result<Event> {
+        read()
+    }
+
+    fn set_raw_mode(&self, raw: bool) {
+        let _ = if raw {
+            enable_raw_mode()
+        } else {
+            disable_raw_mode()
+        };
+    }
+}
+
+/// Plays back a list of events, so games can be tested without a terminal.
+///
+/// Once the events run out it behaves like a terminal nobody types into.
+#[cfg(test)]
+pub struct MockInput {
+    events: RefCell<VecDeque<Event>>,
+    /// Whether keys outside of raw mode only arrive along with an Enter, like on a terminal.
+    cooked: bool,
+    raw: Cell<bool>,
+}
+
+#[cfg(test)]
+impl MockInput {
+    pub fn new(events: impl IntoIterator<Item = Event>) -> MockInput {
+        MockInput {
+            events: RefCell::new(events.into_iter().collect()),
+            cooked: false,
+            raw: Cell::new(false),
+        }
+    }
+
+    /// Presses the keys one after the other, `\n` being Enter and `\x1b` Esc.
+    pub fn keys(keys: &str) -> MockInput {
+        MockInput::new(keys.chars().map(|c| {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\x1b' => KeyCode::Esc,
+                c => KeyCode::Char(c),
+            };
+            Event::Key(KeyEvent::from(code))
+        }))
+    }
+
+    /// Like `keys`, but outside of raw mode every key is followed by an Enter. A terminal in
+    /// that mode only passes keys on once the line is finished, so a player whose key press
+    /// seems to do nothing ends up pressing Enter too.
+    pub fn typed(keys: &str) -> MockInput {
+        MockInput {
+            cooked: true,
+            ..MockInput::keys(keys)
+        }
+    }
+}
+
+#[cfg(test)]
+impl InputSource for MockInput {
+    fn poll(&self, timeout: Duration) -> crossterm::Result<bool> {
+        if self.events.borrow().is_empty() {
+            std::thread::sleep(timeout);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn next_event(&self) -> crossterm::Result<Event> {
+        loop {
+            let mut events = self.events.borrow_mut();
+            if let Some(event) = events.pop_front() {
+                let is_char = matches!(
+                    event,
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char(_),
+                        ..
+                    })
+                );
+                if self.cooked && !self.raw.get() && is_char {
+                    events.push_front(Event::Key(KeyEvent::from(KeyCode::Enter)));
+                }
+                return Ok(event);
+            }
+            drop(events);
+            std::thread::sleep(SIGNAL_CHECK_INTERVAL);
+        }
+    }
+
+    fn set_raw_mode(&self, raw: bool) {
+        self.raw.set(raw);
+    }
+}
+
+/// What came of waiting for a key press with a deadline.
+pub enum TimedKey {
+    Key(KeyCode),
+    TimedOut,
+    /// Stdin ran out of lines in batch mode.
+    Closed,
+    /// SIGTERM arrived while waiting.
+    Terminated,
+}
+
+/// Waits for the next key press. In batch mode each line of stdin counts as one key.
+///
+/// Returns `None` once stdin has run out of lines.
+pub fn read_key(input: &impl InputSource, batch: bool) -> Option<KeyCode> {
+    if batch {
+        let line = read_stdin_line()?;
+        return Some(match line.trim().chars().next() {
+            Some(c) => KeyCode::Char(c),
+            None => KeyCode::Enter,
+        });
+    }
+    let _raw = RawMode::enter(input);
+    loop {
+        match read_event(input) {
+            Ok(Event::Key(key)) => {
+                exit_on_ctrl_c(input, key);
+                return Some(key.code);
+            }
+            Ok(_) => {}
+            Err(_) => println!("There was an error whilst reading input."),
+        }
+    }
+}
+
+/// Like `read_key`, but gives up once the deadline has passed or SIGTERM arrives.
+///
+/// Batch mode has nobody to hurry, so the deadline is ignored there.
+pub fn read_key_before(
+    input: &impl InputSource,
+    deadline: Option<Instant>,
+    batch: bool,
+) -> TimedKey {
+    if batch {
+        return match read_key(input, batch) {
+            _ if terminate_requested() => TimedKey::Terminated,
+            Some(code) => TimedKey::Key(code),
+            None => TimedKey::Closed,
+        };
+    }
+    let _raw = RawMode::enter(input);
+    loop {
+        if terminate_requested() {
+            return TimedKey::Terminated;
+        }
+        //Wake up regularly to notice SIGTERM even without a deadline
+        let wait = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return TimedKey::TimedOut;
+                }
+                remaining.min(SIGNAL_CHECK_INTERVAL)
+            }
+            None => SIGNAL_CHECK_INTERVAL,
+        };
+        match input.poll(wait) {
+            Ok(true) => match input.next_event() {
+                Ok(Event::Key(key)) => {
+                    exit_on_ctrl_c(input, key);
+                    return TimedKey::Key(key.code);
+                }
+                Ok(_) => {}
+                Err(_) => println!("There was an error whilst reading input."),
+            },
+            Ok(false) => {}
+            Err(_) => println!("There was an error whilst reading input."),
+        }
+    }
+}
+
+/// Reads a line of text, returning `None` once stdin has run out of lines in batch mode.
+pub fn read_line(input: &impl InputSource, batch: bool) -> Option<String> {
+    if batch {
+        return read_stdin_line();
+    }
+    let mut line = String::new();
+    loop {
+        match read_event(input) {
+            Ok(Event::Key(KeyEvent { code, .. })) => match code {
+                KeyCode::Enter => return Some(line),
+                KeyCode::Char(c) => line.push(c),
+                KeyCode::Backspace => {
+                    line.pop();
+                }
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(_) => println!("There was an error whilst reading input."),
+        }
+    }
+}
+
+/// Waits for the next terminal event. SIGTERM ends the program like Ctrl-C does.
+fn read_event(input: &impl InputSource) -> crossterm::Result<Event> {
+    loop {
+        if terminate_requested() {
+            save_tracked_game();
+            std::process::exit(0);
+        }
+        if input.poll(SIGNAL_CHECK_INTERVAL)? {
+            return input.next_event();
+        }
+    }
+}
+
+/// Raw mode turns Ctrl-C into a key press, so it has to save and exit like the handler would.
+fn exit_on_ctrl_c(input: &impl InputSource, key: KeyEvent) {
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        input.set_raw_mode(false);
+        save_tracked_game();
+        std::process::exit(0);
+    }
+}
+
+fn read_stdin_line() -> Option<String> {
+    let mut line = String::new();
+    match std::io::stdin().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line),
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local, SecondsFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::dirs::{active_profile, app_dirs};
+use crate::game::GameSummary;
+
+const SCORES_FILENAME: &str = "scores.json";
+/// How many results the leaderboard shows.
+const LEADERBOARD_LENGTH: usize = 10;
+
+/// The result of one player in one game. Shared by all profiles, so they can be compared.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ScoreEntry {
+    pub player: String,
+    pub date: String,
+    pub score: u32,
+    pub correct: u32,
+    pub incorrect: u32,
+    /// Whether the game was a daily challenge. Older files don't have it.
+    #[serde(default)]
+    pub daily: bool,
+}
+
+impl ScoreEntry {
+    fn percentage(&self) -> f64 {
+        let answered = self.correct + self.incorrect;
+        if answered == 0 {
+            0.0
+        } else {
+            f64::from(self.correct) * 100.0 / f64::from(answered)
+        }
+    }
+}
+
+fn scores_path() -> io::Result<PathBuf> {
+    app_dirs().profile_file(SCORES_FILENAME)
+}
+
+fn load_scores() -> io::Result<Vec<ScoreEntry>> {
+    let contents = match fs::read_to_string(scores_path()?) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    if contents.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&contents).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+}
+
+/// Adds the game to the saved scores, one entry per player of a hot-seat game.
+pub fn record_scores(summary: &GameSummary, daily: bool) {
+    let date = Local::now().to_rfc3339_opts(SecondsFormat::Secs, false);
+    let result = load_scores().and_then(|mut scores| {
+        if summary.players.is_empty() {
+            scores.push(ScoreEntry {
+                player: active_profile().to_string(),
+                date: date.clone(),
+                score: summary.points,
+                correct: summary.correct,
+                incorrect: summary.incorrect,
+                daily,
+            });
+        }
+        for player in &summary.players {
+            scores.push(ScoreEntry {
+                player: player.name.clone(),
+                date: date.clone(),
+                score: player.points,
+                correct: player.correct,
+                incorrect: player.incorrect,
+                daily,
+            });
+        }
+        let json = serde_json::to_string_pretty(&scores)
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+        fs::write(scores_path()?, json)
+    });
+    if let Err(err) = result {
+        println!("Warning: could not save the score: {err}");
+    }
+}
+
+/// Prints the best results of all time, by score or by the share of correct answers.
+pub fn print_leaderboard(by_percentage: bool) {
+    let mut scores = match load_scores() {
+        Ok(scores) => scores,
+        Err(err) => {
+            println!("Could not read the scores: {err}");
+            std::process::exit(1);
+        }
+    };
+    if scores.is_empty() {
+        println!("No scores have been saved yet.");
+        return;
+    }
+    if by_percentage {
+        scores.sort_by(|a, b| b.percentage().total_cmp(&a.percentage()));
+    } else {
+        scores.sort_by(|a, b| b.score.cmp(&a.score));
+    }
+
+    println!(
+        "{:>4} {:<16} {:<10} {:>6} {:>8}  {}",
+        "#", "Player", "Date", "Score", "Correct", "Daily"
+    );
+    for (place, entry) in scores.iter().take(LEADERBOARD_LENGTH).enumerate() {
+        let date = match DateTime::parse_from_rfc3339(&entry.date) {
+            Ok(date) => date.format("%Y-%m-%d").to_string(),
+            Err(_) => entry.date.clone(),
+        };
+        println!(
+            "{:>4} {:<16} {:<10} {:>6} {:>7.0}%  {}",
+            place + 1,
+            entry.player,
+            date,
+            entry.score,
+            entry.percentage(),
+            if entry.daily { "yes" } else { "" }
+        );
+    }
+}
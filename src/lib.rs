@@ -0,0 +1,9 @@
+//! Loading and checking questions, shared by the quiz and its integration tests.
+
+pub mod cache;
+pub mod config;
+pub mod dirs;
+pub mod error;
+pub mod question;
+pub mod source;
+pub mod validate;
@@ -1,244 +1,796 @@
 extern crate xml;
 
-use std::fs::File;
-use std::io::BufReader;
+mod achievements;
+mod editor;
+mod export;
+mod game;
+mod history;
+mod input;
+mod leaderboard;
+mod merge;
+mod messages;
+mod new_question;
+mod profiles;
+mod question_stats;
+mod save;
+mod search;
+mod signals;
+mod spaced_repetition;
+mod wrong_log;
 
-use crossterm::event::{read, Event, KeyCode, KeyEvent};
-use crossterm::style::Stylize;
-use rand::{seq::SliceRandom, thread_rng};
-use serde::Deserialize;
-use xml::reader::{EventReader, XmlEvent};
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use chrono::{Datelike, Local};
+use clap::{Parser, Subcommand};
+use crossterm::event::KeyCode;
+use crossterm::style::{Attribute, Color, ContentStyle, StyledContent};
+use crossterm::tty::IsTty;
+use log::LevelFilter;
+use the_quiz::cache::{clear_cache, DEFAULT_CACHE_TTL};
+use the_quiz::{config, dirs, question, source, validate};
+
+use crate::achievements::{print_achievements, update_achievements};
+use crate::config::{load_config, Config, KeyBindings, KeyPreset};
+use crate::dirs::{set_profile, DEFAULT_PROFILE};
+use crate::editor::run_editor;
+use crate::export::{export_csv, export_json};
+use crate::game::{resume_game, run_endless, run_game, run_review, Strictness};
+use crate::history::{
+    append_history, career_stats, played_daily_on, print_history, print_stats, HistoryEntry,
+};
+use crate::input::{read_key, read_line, CrosstermInput, InputSource};
+use crate::leaderboard::{print_leaderboard, record_scores};
+use crate::merge::run_merge;
+use crate::messages::{fill, lang_from_env, set_lang, text, Lang, Msg};
+use crate::new_question::run_new_question;
+use crate::profiles::{delete_profile, list_profiles, parse_profile_name};
+use crate::question::{normalize_text, Difficulty, Question};
+use crate::question_stats::{print_question_stats, update_question_stats};
+use crate::save::{delete_saved_game, load_saved_game, save_tracked_game, GameState};
+use crate::search::{run_search, DEFAULT_MAX_DISTANCE};
+use crate::signals::watch_sigterm;
+use crate::source::{
+    check_api, get_questions_from_anki, get_questions_from_api, get_questions_from_file,
+    get_questions_from_stdin, question_file_path, ApiQuery, LazyApiSource, Source, StdinFormat,
+    API_CATEGORIES, API_PAGE_SIZE, API_URL, DEFAULT_API_TIMEOUT, NO_QUESTIONS_EXIT_CODE,
+};
+use crate::spaced_repetition::update_review_schedule;
+use crate::validate::{run_count, run_validate};
+use crate::wrong_log::{append_wrong_answers, load_wrong_log};
+
+/// How many questions the daily challenge has.
+const DAILY_QUESTIONS: usize = 10;
+
+#[derive(Parser, Debug)]
+#[command(about = "A little quiz game for the terminal")]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Keep history and stats separate for each player
+    #[arg(long, global = true, default_value = DEFAULT_PROFILE, value_parser = parse_profile_name)]
+    profile: String,
+    /// The language of the interface, taken from LANG when not given. English is the fallback
+    #[arg(long, global = true, value_enum)]
+    lang: Option<Lang>,
+    /// Where the questions come from, instead of asking. Repeat it to mix several sources
+    #[arg(long, value_enum)]
+    source: Vec<Source>,
+    /// How many questions to play, and how many to request from the web (10 by default)
+    #[arg(long, visible_alias = "count", value_name = "N")]
+    limit: Option<usize>,
+    /// Keep fetching questions from the web while playing, until quitting or reaching the limit
+    #[arg(long, conflicts_with_all = ["source", "review"])]
+    endless: bool,
+    /// How hard the questions from the web should be
+    #[arg(long, value_enum)]
+    difficulty: Option<Difficulty>,
+    /// Seconds to answer each question
+    #[arg(long, value_name = "SECONDS")]
+    timer: Option<u64>,
+    /// How many tries each question gets, each extra one earning fewer points
+    #[arg(long, value_name = "N")]
+    attempts: Option<u32>,
+    /// Append every wrongly answered question to this file
+    #[arg(long, value_name = "PATH")]
+    wrong_log: Option<PathBuf>,
+    /// Only play the questions from a wrong answer log
+    #[arg(long, value_name = "PATH")]
+    review_wrong_log: Option<PathBuf>,
+    /// Write the questions, answers and tally of the game to a JSON file, never overwriting one
+    #[arg(long, value_name = "FILE")]
+    export: Option<PathBuf>,
+    /// Write the results of every question to a CSV file
+    #[arg(long, value_name = "PATH")]
+    export_csv: Option<PathBuf>,
+    /// Wager points from your bank before each question
+    #[arg(long)]
+    wager: bool,
+    /// Read answers from stdin, one per line, instead of key presses, and leave the terminal
+    /// as it is
+    #[arg(long, visible_alias = "non-interactive")]
+    batch: bool,
+    /// Show the answer options in the order they were written
+    #[arg(long)]
+    no_shuffle: bool,
+    /// Show at most N of the wrong answers of each question, picked at random
+    #[arg(long, value_name = "N")]
+    max_wrong_answers: Option<usize>,
+    /// Together with --seed, show every question with the same options in the same order each time
+    #[arg(long, requires = "seed")]
+    stable_options: bool,
+    /// Ask the questions in the order they were loaded
+    #[arg(long)]
+    no_shuffle_questions: bool,
+    /// Seed the random number generator to replay the same game. Without it a seed is drawn
+    /// and shown
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Print everything without colors
+    #[arg(long)]
+    no_color: bool,
+    /// Write nothing but plain text: no colors, no clearing the screen and no redrawn lines
+    #[arg(long)]
+    plain: bool,
+    /// Send a test request to check that the API key works
+    #[arg(long)]
+    check_api: bool,
+    /// Give partial credit on select-all-that-apply questions
+    #[arg(long)]
+    partial_credit: bool,
+    /// Use a ready-made set of answer keys instead of the keybindings of the config
+    #[arg(long, value_enum, value_name = "PRESET")]
+    answer_keys: Option<KeyPreset>,
+    /// Ask for Enter to lock in a picked answer, so a mistyped key can still be changed
+    #[arg(long)]
+    confirm_answers: bool,
+    /// Follow every 5 correct answers in a row with a harder bonus question worth double points
+    #[arg(long)]
+    bonus_rounds: bool,
+    /// End the game on the first wrong answer
+    #[arg(long)]
+    survival: bool,
+    /// How many seconds to wait for the trivia API
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+    /// How the questions of --source stdin are written
+    #[arg(long, value_enum, default_value_t = StdinFormat::Json)]
+    format: StdinFormat,
+    /// The Anki deck (.apkg) to play with --source anki, asked for if not given
+    #[arg(long, value_name = "PATH")]
+    anki_file: Option<PathBuf>,
+    /// Play the questions of this file when the trivia API times out
+    #[arg(long, value_name = "FILE")]
+    fallback_file: Option<PathBuf>,
+    /// Send API requests through this proxy, like http://proxy.example.com:8080
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+    /// The most questions the trivia API hands out per request, larger counts take several
+    #[arg(long, value_name = "N", default_value_t = API_PAGE_SIZE)]
+    api_page_size: usize,
+    /// How many API requests to send at once when the limit needs several
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    api_parallel: usize,
+    /// Log the raw responses of the trivia API to requests.log in the data directory
+    #[arg(long)]
+    debug_requests: bool,
+    /// How long a response of the trivia API is reused for the same request
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_CACHE_TTL)]
+    cache_ttl: u64,
+    /// Always ask the trivia API instead of reusing a cached response
+    #[arg(long)]
+    no_cache: bool,
+    /// Leave out malformed questions of an XML file instead of stopping at the first error
+    #[arg(long)]
+    skip_invalid: bool,
+    /// Stop on incomplete questions in the questions file instead of leaving them out
+    #[arg(long)]
+    strict: bool,
+    /// Play questions with the same prompt more than once instead of only the first one
+    #[arg(long)]
+    keep_duplicates: bool,
+    /// Only play questions with this tag, can be given more than once to play any of them
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+    /// Ring the terminal bell after each answer, once when it's right and twice when it's wrong
+    #[arg(long)]
+    sound: bool,
+    /// Stay quiet even if the config turns the sound on
+    #[arg(long)]
+    no_sound: bool,
+    /// Take turns with N players on this machine, one question each
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(2..))]
+    hot_seat: Option<u8>,
+    /// Clear the terminal before each question so only the current one is visible
+    #[arg(long)]
+    clear_screen: bool,
+    /// Don't show the running score after each question
+    #[arg(long)]
+    no_live_score: bool,
+    /// Type the answers instead of picking them from the options, ? gives a hint. The timer
+    /// doesn't apply
+    #[arg(long)]
+    type_answers: bool,
+    /// How many typos a typed answer may have
+    #[arg(long, value_enum, default_value_t = Strictness::Normal)]
+    strictness: Strictness,
+    /// Serve harder questions after a run of correct answers and easier ones after a miss
+    #[arg(long)]
+    adaptive: bool,
+    /// Ask the questions you know worst more often
+    #[arg(long)]
+    prioritize_weak: bool,
+    /// Ask the questions that are due for review first, spaced out further the better you know them
+    #[arg(long)]
+    spaced_repetition: bool,
+    /// Show extra details while playing and log what the quiz is doing, for bug reports
+    #[arg(long)]
+    verbose: bool,
+    /// Play today's daily challenge, the same questions for everyone
+    #[arg(long)]
+    daily: bool,
+    /// Read through the questions with their answers, without being asked or scored
+    #[arg(long)]
+    review: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show the results of past games
+    History {
+        /// Only show the N most recent games
+        #[arg(long, value_name = "N")]
+        last: Option<usize>,
+    },
+    /// Show the ten best results of all players
+    Leaderboard {
+        /// Rank by the share of correct answers instead of the score
+        #[arg(long)]
+        by_percentage: bool,
+    },
+    /// Show the questions you miss most often and answer slowest
+    QuestionStats,
+    /// Sum up all past games: totals, accuracy, most missed categories and the trend by month
+    Stats,
+    /// Check a question file for problems without playing it
+    Validate { file: PathBuf },
+    /// Count the valid questions in a file, by category
+    Count { file: PathBuf },
+    /// Edit a question file in a full-screen editor
+    Edit { file: PathBuf },
+    /// Combine question files into one, dropping duplicate questions
+    Merge {
+        /// The file to write, its extension picks the format
+        #[arg(long, value_name = "OUT")]
+        output: PathBuf,
+        /// The XML, JSON or TOML files to combine
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Find the questions of a file whose prompt or answer contains a keyword
+    Search {
+        keyword: String,
+        /// The file to search, questions.xml by default
+        #[arg(long, value_name = "PATH")]
+        file: Option<PathBuf>,
+        /// Print the matching questions as a JSON array
+        #[arg(long)]
+        json: bool,
+        /// Also match prompts that are a few typos away from the keyword
+        #[arg(long)]
+        fuzzy: bool,
+        /// How many edits --fuzzy allows at most, a third of the keyword's length being the limit
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DISTANCE)]
+        max_distance: usize,
+    },
+    /// Write a new question step by step and add it to a question file
+    NewQuestion {
+        /// The file to add the question to, questions.xml by default
+        #[arg(long, value_name = "PATH")]
+        file: Option<PathBuf>,
+    },
+    /// Show the achievements and which of them are unlocked
+    Achievements,
+    /// Manage the player profiles
+    Profiles {
+        #[command(subcommand)]
+        command: ProfilesCommand,
+    },
+    /// Manage the cached responses of the trivia API
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ProfilesCommand {
+    /// List the existing profiles
+    List,
+    /// Delete a profile with all its history and stats
+    Delete {
+        #[arg(value_parser = parse_profile_name)]
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    /// Delete all cached responses
+    Clear,
+}
+
+/// Only the quiz's own debug logs are shown, the libraries it uses are too chatty.
+fn init_logging(verbose: bool) {
+    let level = if verbose {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Off
+    };
+    env_logger::Builder::new()
+        .filter_module(module_path!(), level)
+        .format_timestamp(None)
+        .init();
+}
 
 fn main() {
+    let mut args = Args::parse();
+    init_logging(args.verbose);
+    set_profile(&args.profile);
+    set_lang(args.lang.or_else(lang_from_env).unwrap_or(Lang::En));
+    //Piped input can't produce key events, so fall back to reading lines. Unless the pipe
+    //holds the questions, then the keys still come from the terminal.
+    if !std::io::stdin().is_tty() && !args.source.contains(&Source::Stdin) {
+        args.batch = true;
+    }
+    match &args.command {
+        Some(Command::History { last }) => {
+            print_history(*last);
+            return;
+        }
+        Some(Command::Leaderboard { by_percentage }) => {
+            print_leaderboard(*by_percentage);
+            return;
+        }
+        Some(Command::QuestionStats) => {
+            print_question_stats();
+            return;
+        }
+        Some(Command::Stats) => {
+            print_stats();
+            return;
+        }
+        Some(Command::Validate { file }) => {
+            run_validate(file);
+            return;
+        }
+        Some(Command::Count { file }) => {
+            run_count(file);
+            return;
+        }
+        Some(Command::Edit { file }) => {
+            run_editor(file);
+            return;
+        }
+        Some(Command::Merge { output, files }) => {
+            run_merge(output, files);
+            return;
+        }
+        Some(Command::Search {
+            keyword,
+            file,
+            json,
+            fuzzy,
+            max_distance,
+        }) => {
+            let path = file.clone().unwrap_or_else(question_file_path);
+            run_search(keyword, &path, *json, *fuzzy, *max_distance);
+            return;
+        }
+        Some(Command::NewQuestion { file }) => {
+            let path = file.clone().unwrap_or_else(question_file_path);
+            run_new_question(&path, args.batch);
+            return;
+        }
+        Some(Command::Achievements) => {
+            print_achievements();
+            return;
+        }
+        Some(Command::Profiles { command }) => {
+            match command {
+                ProfilesCommand::List => list_profiles(),
+                ProfilesCommand::Delete { name } => delete_profile(name, args.batch),
+            }
+            return;
+        }
+        Some(Command::Cache {
+            command: CacheCommand::Clear,
+        }) => {
+            match clear_cache() {
+                Ok(count) => println!("Deleted {count} cached responses."),
+                Err(err) => {
+                    println!("Could not clear the cache: {err}");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        None => {}
+    }
+    let mut config = load_config();
+    //Flags win over the config file, which wins over the built-in defaults
+    if let Some(preset) = args.answer_keys {
+        config.keybindings = KeyBindings::preset(preset);
+    }
+    if args.source.is_empty() {
+        args.source.extend(config.source);
+    }
+    args.limit = args.limit.or(config.limit);
+    args.difficulty = args.difficulty.or(config.difficulty);
+    args.timer = args.timer.or(config.timer);
+    args.attempts = args.attempts.or(config.attempts);
+    args.timeout = args.timeout.or(config.api_timeout);
+    args.sound = (args.sound || config.sound) && !args.no_sound;
+    if !config.color {
+        args.no_color = true;
+    }
+    if args.check_api {
+        let authenticated = check_api(
+            &config,
+            args.timeout.unwrap_or(DEFAULT_API_TIMEOUT),
+            args.proxy.as_deref(),
+        );
+        std::process::exit(if authenticated { 0 } else { 1 });
+    }
+    //Honor https://no-color.org and don't write escape codes into files or pipes
+    if std::env::var_os("NO_COLOR").is_some() || !std::io::stdout().is_tty() {
+        args.no_color = true;
+    }
+    if args.plain {
+        args.no_color = true;
+        args.clear_screen = false;
+    }
+
+    if args.daily {
+        let today = Local::now().date_naive();
+        if played_daily_on(today) {
+            println!("{}", text(Msg::DailyDone));
+            return;
+        }
+        //Everyone playing on the same day gets the same seed
+        args.seed =
+            Some(today.year() as u64 * 10000 + today.month() as u64 * 100 + today.day() as u64);
+        args.limit = Some(DAILY_QUESTIONS);
+        args.no_shuffle_questions = false;
+        args.prioritize_weak = false;
+        args.spaced_repetition = false;
+    }
+    //Without a seed one is drawn and shown, so the game can be replayed with --seed
+    if args.seed.is_none() {
+        let seed = rand::random();
+        println!("{}", fill(Msg::SessionSeed, &[&seed]));
+        args.seed = Some(seed);
+    }
+
     ctrlc::set_handler(move || {
+        save_tracked_game();
         std::process::exit(0);
     })
     .expect("Error setting Ctrl-C handler");
+    //SIGTERM is only noted here, the game saves and exits the next time it checks
+    watch_sigterm();
 
-    let questions = get_questions();
-    run_game(questions);
+    let input = CrosstermInput;
+    if args.review {
+        let (_, questions) = get_questions(&args, &config, &input);
+        run_review(questions, &args, &config, &input);
+        return;
+    }
+    let summary = match offer_resume(&input, args.batch) {
+        Some(state) => resume_game(state, &args, &config, &input),
+        None if args.endless => {
+            let mut source = LazyApiSource::new(&config, api_query(&args));
+            run_endless(
+                &mut source,
+                &Source::Web.to_string(),
+                &args,
+                &config,
+                &input,
+            )
+        }
+        None => {
+            let (source, questions) = get_questions(&args, &config, &input);
+            run_game(questions, &source, &args, &config, &input)
+        }
+    };
+    append_history(&HistoryEntry::new(&summary, args.daily, args.seed));
+    record_scores(&summary, args.daily);
+    if args.daily {
+        println!("{}", text(Msg::DailyComplete));
+    }
+    update_achievements(&summary, &career_stats(), args.no_color);
+    update_question_stats(&summary.results);
+    update_review_schedule(&summary.results);
+    if let Some(path) = &args.wrong_log {
+        append_wrong_answers(path, &summary.results);
+    }
+    if let Some(path) = &args.export {
+        export_json(path, &summary);
+    }
+    if let Some(path) = &args.export_csv {
+        export_csv(path, &summary.results);
+    }
 }
 
-fn get_questions() -> Vec<Question> {
-    println!("What question source should be used?");
-    println!("1: File");
-    println!("2: Web");
-
-    loop {
-        match read() {
-            Ok(e) => match e {
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char('1'),
-                    ..
-                }) => {
-                    return get_questions_from_file();
-                }
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char('2'),
-                    ..
-                }) => {
-                    return get_questions_from_api();
-                }
-                _ => {
-                    continue;
-                }
-            },
-            Err(_) => {}
+/// Loads the questions of every chosen source, leaving out prompts that appear more than once.
+///
+/// Returns a name for the sources for the history log along with the questions.
+fn get_questions(
+    args: &Args,
+    config: &Config,
+    input: &impl InputSource,
+) -> (String, Vec<Question>) {
+    if let Some(path) = &args.review_wrong_log {
+        return (Source::WrongLog.to_string(), load_wrong_log(path));
+    }
+    let sources = if args.source.is_empty() {
+        vec![choose_source(input, args.batch)]
+    } else {
+        args.source.clone()
+    };
+    let mut questions = Vec::new();
+    for source in &sources {
+        questions.extend(load_source(*source, args, config, input));
+    }
+    if !args.keep_duplicates {
+        //Prompts only differing in case or spacing are the same, whatever their ids
+        let mut seen = HashSet::new();
+        let before = questions.len();
+        questions.retain(|question| seen.insert(normalize_text(&question.text)));
+        match before - questions.len() {
+            0 => {}
+            1 => println!("{}", text(Msg::RemovedDuplicate)),
+            removed => println!("{}", fill(Msg::RemovedDuplicates, &[&removed])),
         }
     }
+    if !args.tags.is_empty() {
+        questions.retain(|question| {
+            question.tags.iter().any(|tag| {
+                args.tags
+                    .iter()
+                    .any(|wanted| tag.eq_ignore_ascii_case(wanted))
+            })
+        });
+        println!(
+            "{}",
+            fill(
+                Msg::Tagged,
+                &[&questions.len(), &args.tags.join(text(Msg::Or))]
+            )
+        );
+    }
+    let name = sources
+        .iter()
+        .map(Source::to_string)
+        .collect::<Vec<_>>()
+        .join("+");
+    (name, questions)
 }
 
-fn get_questions_from_api() -> Vec<Question> {
-    let res = match reqwest::blocking::get("https://the-trivia-api.com/api/questions?limit=5") {
-        Ok(res) => res,
-        Err(_) => {
-            println!("Error on download");
-            std::process::exit(1)
+/// How many questions are worth reading from the question file. Played in file order and
+/// without a tag filter, the ones after the limit are never asked.
+fn file_question_limit(args: &Args) -> Option<usize> {
+    let in_file_order = args.no_shuffle_questions
+        && !args.prioritize_weak
+        && !args.spaced_repetition
+        && args.tags.is_empty();
+    args.limit.filter(|_| in_file_order)
+}
+
+fn load_source(
+    source: Source,
+    args: &Args,
+    config: &Config,
+    input: &impl InputSource,
+) -> Vec<Question> {
+    match source {
+        Source::File => {
+            get_questions_from_file(args.skip_invalid, args.strict, file_question_limit(args))
         }
-    };
-    let questions: Vec<Question> = match res.json() {
-        Ok(json) => json,
-        Err(err) => {
-            println!("Error on deserialiation: {err}");
-            std::process::exit(1)
+        Source::WrongLog => unreachable!("wrong answer logs are only read with --review-wrong-log"),
+        Source::Stdin => get_questions_from_stdin(args.format),
+        Source::Anki => {
+            let path = match &args.anki_file {
+                Some(path) => path.clone(),
+                None => ask_anki_path(input, args.batch),
+            };
+            match get_questions_from_anki(&path) {
+                Ok(questions) if questions.is_empty() => {
+                    println!("No questions were found in `{}`.", path.display());
+                    std::process::exit(NO_QUESTIONS_EXIT_CODE);
+                }
+                Ok(questions) => questions,
+                Err(err) => {
+                    println!("Could not import {}: {err}", path.display());
+                    std::process::exit(1);
+                }
+            }
         }
-    };
-
-    questions
+        Source::Web => {
+            //Chosen from the menu, the web source asks for the rest too
+            let query = if args.source.is_empty() && !args.batch {
+                ask_web_query(input, args)
+            } else {
+                api_query(args)
+            };
+            get_questions_from_api(config, &query)
+        }
+    }
 }
 
-fn get_questions_from_file() -> Vec<Question> {
-    let parser = load_file();
-    let questions = parse_data(parser);
-    questions
+fn api_query(args: &Args) -> ApiQuery {
+    ApiQuery {
+        base_url: API_URL.to_string(),
+        limit: args.limit,
+        difficulty: args.difficulty,
+        category: None,
+        timeout: args.timeout.unwrap_or(DEFAULT_API_TIMEOUT),
+        fallback_file: args.fallback_file.clone(),
+        proxy: args.proxy.clone(),
+        debug_requests: args.debug_requests,
+        parallel: args.api_parallel,
+        page_size: args.api_page_size,
+        cache_ttl: (!args.no_cache).then_some(args.cache_ttl),
+        plain: args.plain,
+    }
 }
 
-fn load_file() -> EventReader<BufReader<File>> {
-    //Loading the file
-    const FILENAME: &str = "questions.xml";
-    let file = match File::open(FILENAME) {
-        Ok(file) => file,
-        Err(_) => {
-            print!("{} not found. Exiting.", FILENAME);
+/// Asks for the category, difficulty and number of the web questions one after the other, then
+/// shows the request before it is sent. Typing `back` returns to the previous step.
+fn ask_web_query(input: &impl InputSource, args: &Args) -> ApiQuery {
+    let mut query = api_query(args);
+    println!("{}", text(Msg::WebBack));
+    let mut step = 0;
+    while step < 4 {
+        match step {
+            0 => {
+                println!("{}", text(Msg::WebCategory));
+                for (number, category) in API_CATEGORIES.iter().enumerate() {
+                    println!("{}: {}", number + 1, category.replace('_', " "));
+                }
+            }
+            1 => println!("{}", text(Msg::WebDifficulty)),
+            2 => println!("{}", fill(Msg::WebCount, &[&query.wanted()])),
+            _ => println!("{}", fill(Msg::WebConfirm, &[&query.full_url()])),
+        }
+        let Some(line) = read_line(input, args.batch) else {
+            println!("{}", text(Msg::NoWebQuestions));
             std::process::exit(1);
+        };
+        let line = line.trim();
+        if line.eq_ignore_ascii_case("back") {
+            step = step.saturating_sub(1);
+            continue;
         }
-    };
-    //Create Buffer and parser
-    let file = BufReader::new(file);
-
-    EventReader::new(file)
-}
-
-fn parse_data(parser: EventReader<BufReader<File>>) -> Vec<Question> {
-    //Parse Questions
-    let mut data: Vec<Question> = Vec::new();
-    let mut cur_question: Option<Question> = None;
-    let mut cur_data: Option<String> = None;
-
-    for e in parser {
-        match e {
-            Ok(e) => match e {
-                XmlEvent::StartElement { name, .. } => match name.local_name.as_str() {
-                    "question" => cur_question = Some(Question::new()),
-                    "prompt" | "correctAnswer" | "incorrectAnswer" => match cur_question {
-                        Some(_) => cur_data = Some(String::new()),
-                        None => warn_unexpected_tag(name.local_name.as_str(), false),
-                    },
-                    _ => warn_unexpected_tag(name.local_name.as_str(), false),
-                },
-                XmlEvent::EndElement { name } => match name.local_name.as_str() {
-                    "question" => match cur_question {
-                        Some(_) => data.push(cur_question.take().unwrap()),
-                        None => warn_unexpected_tag("question", true),
-                    },
-                    "prompt" | "correctAnswer" | "incorrectAnswer" => match cur_question {
-                        Some(_) => {
-                            let mut question = cur_question.take().unwrap();
-                            let data = cur_data.take().unwrap();
-                            if name.local_name == "prompt" {
-                                question.text = data;
-                            } else if name.local_name == "correctAnswer" {
-                                question.answer = data;
-                            } else if name.local_name == "incorrectAnswer" {
-                                question.wrong_answers.push(data);
-                            }
-                            cur_question = Some(question)
-                        }
-                        None => warn_unexpected_tag(name.local_name.as_str(), true),
-                    },
-                    _ => {}
-                },
-                XmlEvent::Characters(s) => match cur_data {
-                    Some(_) => {
-                        let mut data = cur_data.take().unwrap();
-                        data.push_str(s.as_str());
-                        cur_data = Some(data);
-                    }
-                    None => {
-                        panic!("We should not be getting characters here.")
-                    }
-                },
-                _ => {}
-            },
-            Err(e) => {
-                println!("Error: {}", e);
-                std::process::exit(1);
+        let result = match step {
+            0 => parse_category(line).map(|category| query.category = category),
+            1 if line.is_empty() => {
+                query.difficulty = None;
+                Ok(())
             }
+            1 => line
+                .parse()
+                .map(|difficulty| query.difficulty = Some(difficulty)),
+            2 if line.is_empty() => Ok(()),
+            2 => match line.parse() {
+                Ok(count) if count > 0 => {
+                    query.limit = Some(count);
+                    Ok(())
+                }
+                _ => Err(fill(Msg::NotACount, &[&line])),
+            },
+            _ if line.is_empty() => Ok(()),
+            _ => Err(text(Msg::WebConfirmAgain).to_string()),
+        };
+        match result {
+            Ok(()) => step += 1,
+            Err(message) => println!("{message}"),
         }
     }
-
-    data
+    query
 }
 
-fn warn_unexpected_tag(name: &str, closing: bool) {
-    if closing {
-        println!("Unexpected closing {name} tag.")
-    } else {
-        println!("Unexpected {name} tag.")
+/// Reads a category of the API by its number in the list or its name. Nothing means any.
+fn parse_category(text: &str) -> Result<Option<String>, String> {
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let by_number = text
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| API_CATEGORIES.get(number.checked_sub(1)?));
+    let slug = text.to_lowercase().replace(' ', "_");
+    let by_name = API_CATEGORIES.iter().find(|category| **category == slug);
+    match by_number.or(by_name) {
+        Some(category) => Ok(Some(category.to_string())),
+        None => Err(fill(Msg::NoCategory, &[&text])),
     }
 }
 
-fn run_game(questions: Vec<Question>) {
-    let mut rng = thread_rng();
-    let mut answered_correctly = 0;
-    let mut answered_incorrectly = 0;
-
-    for q in questions {
-        println!(" === {} ===", q.text);
-
-        //Shuffle the order of the answers and display them
-        let mut correct_answer = 0;
-        let mut options: Vec<usize> = (0..q.wrong_answers.len() + 1).collect();
-        options.shuffle(&mut rng);
-        for (index, order) in options.iter().enumerate() {
-            if order == &q.wrong_answers.len() {
-                println!("{}: {}", index + 1, q.answer);
-                correct_answer = index;
-            } else {
-                println!("{}: {}", index + 1, q.wrong_answers[order.clone()])
+/// Asks whether a saved game should be resumed. Declining throws the saved game away.
+fn offer_resume(input: &impl InputSource, batch: bool) -> Option<GameState> {
+    let state = load_saved_game()?;
+    println!("{}", text(Msg::ResumeSaved));
+    loop {
+        match read_key(input, batch) {
+            Some(KeyCode::Char('y')) => return Some(state),
+            Some(KeyCode::Char('n')) | None => {
+                delete_saved_game();
+                return None;
             }
+            Some(_) => continue,
         }
+    }
+}
 
-        let answer;
-        //Read the users response
-        'input: loop {
-            match read() {
-                Ok(e) => match e {
-                    Event::Key(event) => {
-                        //Check options for if that the one the user pressed
-                        for option in options.clone() {
-                            //Convert the option to a char
-                            let option_char =
-                                char::from_digit((option + 1).try_into().unwrap(), 10)
-                                    .expect("Could not convert option to character.");
-                            if event.code == KeyCode::Char(option_char) {
-                                answer = option;
-                                break 'input;
-                            }
-                        }
-                    }
-                    _ => {}
-                },
-                Err(_) => {
-                    println!("There was an error whilst reading the answer.")
-                }
-            }
+fn ask_anki_path(input: &impl InputSource, batch: bool) -> PathBuf {
+    println!("{}", text(Msg::AnkiPath));
+    match read_line(input, batch) {
+        Some(line) if !line.trim().is_empty() => PathBuf::from(line.trim()),
+        _ => {
+            println!("{}", text(Msg::NoDeck));
+            std::process::exit(1);
         }
+    }
+}
 
-        //Show if they got it right or not
-        if answer == correct_answer {
-            println!("{}", "Correct!".green());
-            answered_correctly += 1;
-        } else {
-            println!("{} The correct answer is: {}", "Wrong!".red(), q.answer);
-            answered_incorrectly += 1;
+fn choose_source(input: &impl InputSource, batch: bool) -> Source {
+    println!("{}", text(Msg::ChooseSource));
+    println!("1: {}", text(Msg::SourceFile));
+    println!("2: {}", text(Msg::SourceWeb));
+    println!("3: {}", text(Msg::SourceAnki));
+
+    loop {
+        match read_key(input, batch) {
+            Some(KeyCode::Char('1')) => {
+                return Source::File;
+            }
+            Some(KeyCode::Char('2')) => {
+                return Source::Web;
+            }
+            Some(KeyCode::Char('3')) => {
+                return Source::Anki;
+            }
+            Some(_) => {
+                continue;
+            }
+            None => {
+                println!("{}", text(Msg::NoSourceChosen));
+                std::process::exit(1);
+            }
         }
-        println!();
     }
-
-    println!(
-        "That's it! You answered {} questions correctly and {} incorrectly.",
-        answered_correctly.to_string().green(),
-        answered_incorrectly.to_string().red()
-    );
 }
 
-#[derive(Clone, Debug, Deserialize)]
-struct Question {
-    #[serde(alias = "question")]
-    text: String,
-    #[serde(alias = "correctAnswer")]
-    answer: String,
-    #[serde(alias = "incorrectAnswers")]
-    wrong_answers: Vec<String>,
+/// Colors the text, unless the output is plain, with `--plain` or with colors turned off.
+fn styled_or_plain(text: &str, color: Color, plain: bool) -> impl Display + '_ {
+    emphasized_or_plain(text, color, None, plain)
 }
 
-impl Question {
-    fn new() -> Question {
-        Question {
-            text: String::new(),
-            answer: String::new(),
-            wrong_answers: Vec::new(),
+/// Like `styled_or_plain`, with an attribute like bold on top of the color.
+fn emphasized_or_plain(
+    text: &str,
+    color: Color,
+    attribute: Option<Attribute>,
+    plain: bool,
+) -> impl Display + '_ {
+    let mut style = ContentStyle::new();
+    if !plain {
+        style.foreground_color = Some(color);
+        if let Some(attribute) = attribute {
+            style.attributes.set(attribute);
         }
     }
+    StyledContent::new(style, text)
 }
@@ -0,0 +1,34 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::question::{normalize_text, Question};
+use crate::source::{get_questions_from_path, save_questions_to_path};
+
+/// Combines question files into one, keeping only the first of the questions with the same text,
+/// ignoring case and spacing.
+///
+/// Every file has to load, a broken one ends the program before anything is written.
+pub fn run_merge(output: &Path, files: &[PathBuf]) {
+    let mut seen = HashSet::new();
+    let mut merged: Vec<Question> = Vec::new();
+    let mut duplicates = 0;
+    for file in files {
+        let questions = get_questions_from_path(file, false);
+        println!("{}: {} questions", file.display(), questions.len());
+        for question in questions {
+            if seen.insert(normalize_text(&question.text)) {
+                merged.push(question);
+            } else {
+                duplicates += 1;
+            }
+        }
+    }
+    println!("Removed {duplicates} duplicates.");
+    match save_questions_to_path(output, &merged) {
+        Ok(()) => println!("Wrote {} questions to {}", merged.len(), output.display()),
+        Err(err) => {
+            println!("Could not write {}: {err}", output.display());
+            std::process::exit(1);
+        }
+    }
+}
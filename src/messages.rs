@@ -0,0 +1,356 @@
+use std::env;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+
+/// The languages the interface can be shown in. The questions stay as they are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    En,
+    De,
+}
+
+/// The language chosen with `--lang` or `LANG`, set once at startup.
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// Chooses the language for this run. Only the first call has an effect.
+pub fn set_lang(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+
+/// The language named by the `LANG` environment variable, like `de_DE.UTF-8`, if there are
+/// texts for it.
+pub fn lang_from_env() -> Option<Lang> {
+    let value = env::var("LANG").ok()?;
+    let code = value.split(['_', '.', '-']).next()?;
+    Lang::from_str(code, true).ok()
+}
+
+/// Declares `Msg` along with `Msg::ALL`, so no message can be left out of the list.
+macro_rules! messages {
+    ($($msg:ident,)*) => {
+        /// The texts of the interface. `{}` in a text marks where `fill` puts a value.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum Msg {
+            $($msg,)*
+        }
+
+        impl Msg {
+            #[cfg(test)]
+            const ALL: &'static [Msg] = &[$(Msg::$msg,)*];
+        }
+    };
+}
+
+messages! {
+    Correct,
+    Wrong,
+    CorrectAnswerIs,
+    PartiallyCorrect,
+    FoundSome,
+    TimesUp,
+    Skipped,
+    TryAgain,
+    TriesLeft,
+    SelectAll,
+    YouHaveSeconds,
+    TypeAnswer,
+    CloseEnough,
+    SpelledAs,
+    Hint,
+    NoMoreHints,
+    Paused,
+    Resumed,
+    LiveScore,
+    Summary,
+    PercentCorrect,
+    Scored,
+    ScoredWeighted,
+    HintsUsed,
+    PartiallyRight,
+    SkippedCount,
+    ChooseSource,
+    SourceFile,
+    SourceWeb,
+    SourceAnki,
+    NoSourceChosen,
+    RemovedDuplicate,
+    RemovedDuplicates,
+    Tagged,
+    Or,
+    Category,
+    Answer,
+    NextQuestion,
+    SkippingUnplayable,
+    NoQuestions,
+    YourTurn,
+    YourBank,
+    BonusDouble,
+    QuestionId,
+    Mastery,
+    NotAnsweredBefore,
+    DuplicateOption,
+    DuplicateOptions,
+    Selected,
+    PendingConfirm,
+    Survived,
+    FinalBank,
+    PlayerName,
+    DefaultPlayer,
+    Ranking,
+    RankingLine,
+    GameSaved,
+    CouldNotSave,
+    PreviousQuestion,
+    SkippedShort,
+    BonusHarder,
+    BonusPoints,
+    AverageTime,
+    Fastest,
+    Slowest,
+    ReadMore,
+    CouldNotOpen,
+    Wager,
+    WagerRange,
+    DailyDone,
+    DailyComplete,
+    SessionSeed,
+    ResumeSaved,
+    AnkiPath,
+    NoDeck,
+    WebBack,
+    WebCategory,
+    WebDifficulty,
+    WebCount,
+    WebConfirm,
+    WebConfirmAgain,
+    NoWebQuestions,
+    NotACount,
+    NoCategory,
+}
+
+/// The text of the message in the chosen language.
+pub fn text(msg: Msg) -> &'static str {
+    match LANG.get().copied().unwrap_or(Lang::En) {
+        Lang::En => english(msg),
+        Lang::De => german(msg),
+    }
+}
+
+/// The text of the message with each `{}` replaced by the next of the values.
+pub fn fill(msg: Msg, values: &[&dyn Display]) -> String {
+    let mut parts = text(msg).split("{}");
+    let mut filled = parts.next().unwrap_or_default().to_string();
+    for (part, value) in parts.zip(values) {
+        filled.push_str(&value.to_string());
+        filled.push_str(part);
+    }
+    filled
+}
+
+fn english(msg: Msg) -> &'static str {
+    match msg {
+        Msg::Correct => "Correct!",
+        Msg::Wrong => "Wrong!",
+        Msg::CorrectAnswerIs => "The correct answer is: {}",
+        Msg::PartiallyCorrect => "Partially correct!",
+        Msg::FoundSome => "You found {} of {}. The correct answers are: {}",
+        Msg::TimesUp => "Time's up!",
+        Msg::Skipped => "Skipped. The correct answer is: {}",
+        Msg::TryAgain => "Try again!",
+        Msg::TriesLeft => "{} tries left.",
+        Msg::SelectAll => "Select all answers that apply, then press Enter.",
+        Msg::YouHaveSeconds => "You have {} seconds.",
+        Msg::TypeAnswer => "Type your answer, or ? for a hint:",
+        Msg::CloseEnough => "Close enough!",
+        Msg::SpelledAs => "It's spelled {}.",
+        Msg::Hint => "Hint (-{} points): {}",
+        Msg::NoMoreHints => "There are no more hints for this question.",
+        Msg::Paused => "Paused - press any key to resume",
+        Msg::Resumed => "Resumed, {} seconds left.",
+        Msg::LiveScore => "Score: {} ✓ / {} ✗ ({} points)",
+        Msg::Summary => "That's it! You answered {} questions correctly and {} incorrectly.",
+        Msg::PercentCorrect => "That's {}% correct.",
+        Msg::Scored => "You scored {} points.",
+        Msg::ScoredWeighted => "You scored {} points, weighted by difficulty.",
+        Msg::HintsUsed => "You used {} hints.",
+        Msg::PartiallyRight => "You got {} questions partially right.",
+        Msg::SkippedCount => "You skipped {} questions.",
+        Msg::ChooseSource => "What question source should be used?",
+        Msg::SourceFile => "File",
+        Msg::SourceWeb => "Web",
+        Msg::SourceAnki => "Anki deck",
+        Msg::NoSourceChosen => "No question source was chosen. Exiting.",
+        Msg::RemovedDuplicate => "Removed 1 duplicate question.",
+        Msg::RemovedDuplicates => "Removed {} duplicate questions.",
+        Msg::Tagged => "{} questions are tagged {}.",
+        Msg::Or => " or ",
+        Msg::Category => "Category: {}",
+        Msg::Answer => "Answer: {}",
+        Msg::NextQuestion => "Press any key for the next question.",
+        Msg::SkippingUnplayable => {
+            "Skipping \"{}\": it needs a correct and at least one wrong answer."
+        }
+        Msg::NoQuestions => "No questions to play.",
+        Msg::YourTurn => "{}, it's your turn!",
+        Msg::YourBank => "Your bank: {}",
+        Msg::BonusDouble => "{} Double points for this one.",
+        Msg::QuestionId => "Id: {}",
+        Msg::Mastery => "Mastery: {}%",
+        Msg::NotAnsweredBefore => "Mastery: not answered before",
+        Msg::DuplicateOption => "Warning: \"{}\" lists {} more than once, showing it once.",
+        Msg::DuplicateOptions => "Warning: \"{}\" lists {} more than once, showing each once.",
+        Msg::Selected => "Selected: {}",
+        Msg::PendingConfirm => "{} - Enter to lock it in, or pick another option.",
+        Msg::Survived => "You survived {} questions in a row!",
+        Msg::FinalBank => "You finished with a bank of {}.",
+        Msg::PlayerName => "Name of player {}:",
+        Msg::DefaultPlayer => "Player {}",
+        Msg::Ranking => "Ranking:",
+        Msg::RankingLine => "{}. {} with {} points ({} ✓ / {} ✗)",
+        Msg::GameSaved => "Game saved. Start the quiz again to resume it.",
+        Msg::CouldNotSave => "Could not save the game: {}",
+        Msg::PreviousQuestion => "Previous question: {} {} The correct answer was: {}",
+        Msg::SkippedShort => "Skipped.",
+        Msg::BonusHarder => "{} {} in a row, a harder question is coming up.",
+        Msg::BonusPoints => "{} {} in a row, that's {} extra points.",
+        Msg::AverageTime => "Average time per question: {}s",
+        Msg::Fastest => "Fastest: {}s ({})",
+        Msg::Slowest => "Slowest: {}s ({})",
+        Msg::ReadMore => "Press 'o' to read more at {}, or any other key to continue.",
+        Msg::CouldNotOpen => "Could not open {}: {}",
+        Msg::Wager => "How much do you want to wager? (0-{})",
+        Msg::WagerRange => "Please enter a number between 0 and {}.",
+        Msg::DailyDone => "You already finished today's daily challenge. Come back tomorrow!",
+        Msg::DailyComplete => "Daily challenge complete — come back tomorrow!",
+        Msg::SessionSeed => "Session seed: {}",
+        Msg::ResumeSaved => "A saved game was found. Resume? (y/n)",
+        Msg::AnkiPath => "Path of the Anki deck (.apkg):",
+        Msg::NoDeck => "No deck was given. Exiting.",
+        Msg::WebBack => "Type back at any step to change the previous answer.",
+        Msg::WebCategory => "Which category? Type its number or name, or press Enter for any.",
+        Msg::WebDifficulty => "How hard? Type easy, medium or hard, or press Enter for any.",
+        Msg::WebCount => "How many questions? Press Enter for {}.",
+        Msg::WebConfirm => "The questions will come from {}\nPress Enter to get them.",
+        Msg::WebConfirmAgain => {
+            "Press Enter to get the questions, or type back to change something."
+        }
+        Msg::NoWebQuestions => "No questions were chosen. Exiting.",
+        Msg::NotACount => "\"{}\" is not a number of questions.",
+        Msg::NoCategory => "There is no category \"{}\".",
+    }
+}
+
+fn german(msg: Msg) -> &'static str {
+    match msg {
+        Msg::Correct => "Richtig!",
+        Msg::Wrong => "Falsch!",
+        Msg::CorrectAnswerIs => "Die richtige Antwort ist: {}",
+        Msg::PartiallyCorrect => "Teilweise richtig!",
+        Msg::FoundSome => "Du hast {} von {} gefunden. Die richtigen Antworten sind: {}",
+        Msg::TimesUp => "Die Zeit ist um!",
+        Msg::Skipped => "Übersprungen. Die richtige Antwort ist: {}",
+        Msg::TryAgain => "Versuch es noch einmal!",
+        Msg::TriesLeft => "Noch {} Versuche.",
+        Msg::SelectAll => "Wähle alle passenden Antworten und drücke dann Enter.",
+        Msg::YouHaveSeconds => "Du hast {} Sekunden.",
+        Msg::TypeAnswer => "Tippe deine Antwort, oder ? für einen Tipp:",
+        Msg::CloseEnough => "Fast!",
+        Msg::SpelledAs => "Man schreibt es {}.",
+        Msg::Hint => "Tipp (-{} Punkte): {}",
+        Msg::NoMoreHints => "Für diese Frage gibt es keine Tipps mehr.",
+        Msg::Paused => "Pause - drücke eine beliebige Taste, um weiterzuspielen",
+        Msg::Resumed => "Weiter geht's, noch {} Sekunden.",
+        Msg::LiveScore => "Stand: {} ✓ / {} ✗ ({} Punkte)",
+        Msg::Summary => "Geschafft! Du hast {} Fragen richtig und {} falsch beantwortet.",
+        Msg::PercentCorrect => "Das sind {}% richtig.",
+        Msg::Scored => "Du hast {} Punkte erreicht.",
+        Msg::ScoredWeighted => "Du hast {} Punkte erreicht, gewichtet nach Schwierigkeit.",
+        Msg::HintsUsed => "Du hast {} Tipps benutzt.",
+        Msg::PartiallyRight => "Du hast {} Fragen teilweise richtig beantwortet.",
+        Msg::SkippedCount => "Du hast {} Fragen übersprungen.",
+        Msg::ChooseSource => "Woher sollen die Fragen kommen?",
+        Msg::SourceFile => "Datei",
+        Msg::SourceWeb => "Internet",
+        Msg::SourceAnki => "Anki-Stapel",
+        Msg::NoSourceChosen => "Es wurde keine Fragenquelle gewählt. Das Quiz wird beendet.",
+        Msg::RemovedDuplicate => "1 doppelte Frage wurde entfernt.",
+        Msg::RemovedDuplicates => "{} doppelte Fragen wurden entfernt.",
+        Msg::Tagged => "{} Fragen haben das Tag {}.",
+        Msg::Or => " oder ",
+        Msg::Category => "Kategorie: {}",
+        Msg::Answer => "Antwort: {}",
+        Msg::NextQuestion => "Drücke eine beliebige Taste für die nächste Frage.",
+        Msg::SkippingUnplayable => "\"{}\" wird übersprungen: Die Frage braucht eine richtige und mindestens eine falsche Antwort.",
+        Msg::NoQuestions => "Es gibt keine Fragen zum Spielen.",
+        Msg::YourTurn => "{}, du bist dran!",
+        Msg::YourBank => "Dein Konto: {}",
+        Msg::BonusDouble => "{} Für diese Frage gibt es doppelte Punkte.",
+        Msg::QuestionId => "Kennung: {}",
+        Msg::Mastery => "Beherrschung: {}%",
+        Msg::NotAnsweredBefore => "Beherrschung: noch nie beantwortet",
+        Msg::DuplicateOption => "Warnung: \"{}\" nennt {} mehrmals, es wird nur einmal angezeigt.",
+        Msg::DuplicateOptions => "Warnung: \"{}\" nennt {} mehrmals, jede wird nur einmal angezeigt.",
+        Msg::Selected => "Ausgewählt: {}",
+        Msg::PendingConfirm => "{} - Enter zum Bestätigen, oder wähle eine andere Antwort.",
+        Msg::Survived => "Du hast {} Fragen in Folge überstanden!",
+        Msg::FinalBank => "Am Ende steht dein Konto bei {}.",
+        Msg::PlayerName => "Name von Spieler {}:",
+        Msg::DefaultPlayer => "Spieler {}",
+        Msg::Ranking => "Rangliste:",
+        Msg::RankingLine => "{}. {} mit {} Punkten ({} ✓ / {} ✗)",
+        Msg::GameSaved => "Spiel gespeichert. Starte das Quiz erneut, um weiterzuspielen.",
+        Msg::CouldNotSave => "Das Spiel konnte nicht gespeichert werden: {}",
+        Msg::PreviousQuestion => "Vorherige Frage: {} {} Die richtige Antwort war: {}",
+        Msg::SkippedShort => "Übersprungen.",
+        Msg::BonusHarder => "{} {} in Folge, jetzt kommt eine schwerere Frage.",
+        Msg::BonusPoints => "{} {} in Folge, das gibt {} Extrapunkte.",
+        Msg::AverageTime => "Durchschnittliche Zeit pro Frage: {}s",
+        Msg::Fastest => "Am schnellsten: {}s ({})",
+        Msg::Slowest => "Am langsamsten: {}s ({})",
+        Msg::ReadMore => "Drücke 'o', um bei {} mehr zu lesen, oder eine andere Taste, um weiterzuspielen.",
+        Msg::CouldNotOpen => "{} konnte nicht geöffnet werden: {}",
+        Msg::Wager => "Wie viel willst du setzen? (0-{})",
+        Msg::WagerRange => "Bitte gib eine Zahl zwischen 0 und {} ein.",
+        Msg::DailyDone => "Du hast die heutige Tagesaufgabe schon gelöst. Komm morgen wieder!",
+        Msg::DailyComplete => "Tagesaufgabe geschafft – komm morgen wieder!",
+        Msg::SessionSeed => "Seed dieser Runde: {}",
+        Msg::ResumeSaved => "Ein gespeichertes Spiel wurde gefunden. Fortsetzen? (y/n)",
+        Msg::AnkiPath => "Pfad zum Anki-Stapel (.apkg):",
+        Msg::NoDeck => "Es wurde kein Stapel angegeben. Das Quiz wird beendet.",
+        Msg::WebBack => "Tippe bei jedem Schritt back, um die vorige Antwort zu ändern.",
+        Msg::WebCategory => "Welche Kategorie? Tippe ihre Nummer oder ihren Namen, oder drücke Enter für alle.",
+        Msg::WebDifficulty => "Wie schwer? Tippe easy, medium oder hard, oder drücke Enter für alle.",
+        Msg::WebCount => "Wie viele Fragen? Drücke Enter für {}.",
+        Msg::WebConfirm => "Die Fragen kommen von {}\nDrücke Enter, um sie zu laden.",
+        Msg::WebConfirmAgain => "Drücke Enter, um die Fragen zu laden, oder tippe back, um etwas zu ändern.",
+        Msg::NoWebQuestions => "Es wurden keine Fragen gewählt. Das Quiz wird beendet.",
+        Msg::NotACount => "\"{}\" ist keine Anzahl von Fragen.",
+        Msg::NoCategory => "Es gibt keine Kategorie \"{}\".",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translations_keep_the_placeholders() {
+        for msg in Msg::ALL {
+            assert_eq!(
+                german(*msg).matches("{}").count(),
+                english(*msg).matches("{}").count(),
+                "{msg:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn fills_in_the_values_in_order() {
+        assert_eq!(
+            fill(Msg::Summary, &[&3, &1]),
+            "That's it! You answered 3 questions correctly and 1 incorrectly."
+        );
+    }
+}
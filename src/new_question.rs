@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use crossterm::event::KeyCode;
+
+use crate::input::{read_key, read_line, CrosstermInput};
+use crate::question::Question;
+use crate::source::{get_questions_from_path, save_questions_to_path};
+
+/// Asks for a question step by step and adds it to the file after a preview.
+pub fn run_new_question(path: &Path, batch: bool) {
+    let mut questions = if path.exists() {
+        get_questions_from_path(path, false)
+    } else {
+        Vec::new()
+    };
+
+    let mut question = Question::new();
+    question.text = ask("Question text:", batch);
+    question.answer = ask("Correct answer:", batch);
+    println!("Wrong answers, one per line. Press Enter on an empty line when you're done.");
+    loop {
+        let answer = read_answer(batch).unwrap_or_default();
+        if !answer.is_empty() {
+            question.wrong_answers.push(answer);
+        } else if question.wrong_answers.is_empty() {
+            println!("A question needs at least one wrong answer.");
+        } else {
+            break;
+        }
+    }
+
+    println!();
+    println!(" === {} ===", question.text);
+    println!("  ✓ {}", question.answer);
+    for answer in &question.wrong_answers {
+        println!("  ✗ {answer}");
+    }
+    println!("Add this question to {}? (y/n)", path.display());
+    loop {
+        match read_key(&CrosstermInput, batch) {
+            Some(KeyCode::Char('y')) => break,
+            Some(KeyCode::Char('n')) | None => {
+                println!("The question was not added.");
+                return;
+            }
+            Some(_) => continue,
+        }
+    }
+
+    questions.push(question);
+    match save_questions_to_path(path, &questions) {
+        Ok(()) => println!(
+            "Question added. File now contains {} questions.",
+            questions.len()
+        ),
+        Err(err) => {
+            println!("Could not write {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Asks until something other than an empty line comes back.
+fn ask(prompt: &str, batch: bool) -> String {
+    println!("{prompt}");
+    loop {
+        let Some(answer) = read_answer(batch) else {
+            println!("The question was not added.");
+            std::process::exit(1);
+        };
+        if !answer.is_empty() {
+            return answer;
+        }
+        println!("This can't be empty.");
+    }
+}
+
+fn read_answer(batch: bool) -> Option<String> {
+    read_line(&CrosstermInput, batch).map(|line| line.trim().to_string())
+}
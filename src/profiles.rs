@@ -0,0 +1,85 @@
+use std::fs;
+use std::io::ErrorKind;
+
+use crossterm::event::KeyCode;
+
+use crate::dirs::{active_profile, app_dirs};
+use crate::input::{read_key, CrosstermInput};
+
+/// Checks that a profile name is usable as a directory name.
+pub fn parse_profile_name(name: &str) -> Result<String, String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err(String::from(
+            "profile names may only contain letters, digits, '-' and '_'",
+        ))
+    }
+}
+
+pub fn list_profiles() {
+    let entries = match fs::read_dir(app_dirs().profiles_dir()) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            println!("There are no profiles yet.");
+            return;
+        }
+        Err(err) => {
+            println!("Could not read the profiles: {err}");
+            std::process::exit(1);
+        }
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    if names.is_empty() {
+        println!("There are no profiles yet.");
+    }
+    for name in names {
+        if name == active_profile() {
+            println!("{name} (active)");
+        } else {
+            println!("{name}");
+        }
+    }
+}
+
+/// Deletes a profile and everything in it after asking for confirmation.
+pub fn delete_profile(name: &str, batch: bool) {
+    let profiles_dir = app_dirs().profiles_dir();
+    let dir = profiles_dir.join(name);
+    //Never delete anything but a direct child of the profiles directory
+    if parse_profile_name(name).is_err() || dir.parent() != Some(profiles_dir.as_path()) {
+        println!("{name} is not a valid profile name.");
+        std::process::exit(1);
+    }
+    if !dir.is_dir() {
+        println!("There is no profile called {name}.");
+        std::process::exit(1);
+    }
+    println!("Delete the profile {name} with all its history and stats? (y/n)");
+    loop {
+        match read_key(&CrosstermInput, batch) {
+            Some(KeyCode::Char('y')) => break,
+            Some(KeyCode::Char('n')) | None => {
+                println!("Nothing was deleted.");
+                return;
+            }
+            Some(_) => continue,
+        }
+    }
+    match fs::remove_dir_all(&dir) {
+        Ok(()) => println!("Deleted the profile {name}."),
+        Err(err) => {
+            println!("Could not delete {}: {err}", dir.display());
+            std::process::exit(1);
+        }
+    }
+}
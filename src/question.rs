@@ -0,0 +1,200 @@
+use std::fmt;
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    /// What the points of a correct answer are multiplied by.
+    pub fn weight(self) -> u32 {
+        self.level() as u32 + 1
+    }
+
+    /// 0 for easy up to 2 for hard.
+    pub fn level(self) -> usize {
+        match self {
+            Difficulty::Easy => 0,
+            Difficulty::Medium => 1,
+            Difficulty::Hard => 2,
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.to_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!(
+                "Unknown difficulty \"{text}\", expected easy, medium or hard."
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Question {
+    /// Identifies the question in scores and bug reports.
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(alias = "question")]
+    pub text: String,
+    #[serde(alias = "correctAnswer")]
+    pub answer: String,
+    /// Further correct answers, which turn the question into a select-all-that-apply one.
+    #[serde(default, alias = "additionalCorrectAnswers")]
+    pub extra_answers: Vec<String>,
+    #[serde(alias = "incorrectAnswers")]
+    pub wrong_answers: Vec<String>,
+    /// Other spellings of the answer that count when it is typed, like "USA" for "United States".
+    #[serde(default, alias = "acceptableAnswers")]
+    pub acceptable_answers: Vec<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub difficulty: Option<Difficulty>,
+    /// Why the answer is right, shown once the question is answered.
+    #[serde(default)]
+    pub explanation: Option<String>,
+    /// Topics like "capitals" or "ww2", finer grained than the category.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Whether the options get shuffled, off for questions where their order matters.
+    #[serde(default = "shuffled", skip_serializing_if = "is_shuffled")]
+    pub shuffle: bool,
+}
+
+fn shuffled() -> bool {
+    true
+}
+
+fn is_shuffled(shuffle: &bool) -> bool {
+    *shuffle
+}
+
+impl Question {
+    pub fn new() -> Question {
+        Question {
+            id: None,
+            text: String::new(),
+            answer: String::new(),
+            extra_answers: Vec::new(),
+            wrong_answers: Vec::new(),
+            acceptable_answers: Vec::new(),
+            source: None,
+            category: None,
+            difficulty: None,
+            explanation: None,
+            tags: Vec::new(),
+            shuffle: true,
+        }
+    }
+
+    /// All answers that have to be selected, starting with the main one.
+    pub fn correct_answers(&self) -> Vec<&str> {
+        std::iter::once(self.answer.as_str())
+            .chain(self.extra_answers.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// How many options the question shows, correct and wrong ones together.
+    pub fn option_count(&self) -> usize {
+        self.correct_answers().len() + self.wrong_answers.len()
+    }
+
+    pub fn is_multi_select(&self) -> bool {
+        !self.extra_answers.is_empty()
+    }
+
+    /// The prompt, followed by the id if the question has one.
+    pub fn label(&self) -> String {
+        match &self.id {
+            Some(id) => format!("{} [{id}]", self.text),
+            None => self.text.clone(),
+        }
+    }
+
+    /// The key this question's stats and review schedule are stored under, and what tells
+    /// duplicates apart. Its id if it has one, so rewording the prompt keeps the stats.
+    pub fn stats_key(&self) -> String {
+        match &self.id {
+            Some(id) => id.clone(),
+            None => normalize_text(&self.text),
+        }
+    }
+}
+
+/// Lowercases and collapses whitespace so small edits to a prompt still match.
+pub fn normalize_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Brings a typed answer into a form where spelling variants compare equal. On top of what
+/// `normalize_text` does, it composes characters the same way (NFC) and drops accents, so
+/// "Café", "café" and "cafe" all match.
+pub fn normalize_answer(text: &str) -> String {
+    let folded: String = text
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .nfc()
+        .flat_map(|c| c.to_lowercase())
+        .flat_map(|c| match c {
+            'ß' => "ss".chars().collect(),
+            'æ' => "ae".chars().collect(),
+            'œ' => "oe".chars().collect(),
+            'ø' => vec!['o'],
+            'ł' => vec!['l'],
+            'đ' => vec!['d'],
+            _ => vec![c],
+        })
+        .collect();
+    normalize_text(&folded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composed_and_decomposed_accents_match() {
+        assert_eq!(
+            normalize_answer("Caf\u{e9}"),
+            normalize_answer("Cafe\u{301}")
+        );
+    }
+
+    #[test]
+    fn accents_and_ligatures_fold_to_ascii() {
+        assert_eq!(normalize_answer("  Straße  "), "strasse");
+        assert_eq!(normalize_answer("Crème Brûlée"), "creme brulee");
+        assert_eq!(normalize_answer("Ærøskøbing"), "aeroskobing");
+    }
+}
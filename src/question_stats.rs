@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dirs::app_dirs;
+use crate::game::{Outcome, QuestionResult};
+use crate::question::normalize_text;
+
+const STATS_FILENAME: &str = "question_stats.json";
+/// How many of the latest results the mastery score looks at.
+const MASTERY_WINDOW: usize = 10;
+/// How many questions each list of `question-stats` shows.
+const REPORT_LENGTH: usize = 10;
+
+/// How a question has gone across all sessions.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct QuestionStats {
+    /// The prompt as it was last asked, for display.
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    pub correct: u32,
+    pub incorrect: u32,
+    pub avg_time_ms: u32,
+    /// The latest results, oldest first, whether each was answered correctly.
+    #[serde(default)]
+    pub recent: Vec<bool>,
+}
+
+impl QuestionStats {
+    fn record(&mut self, result: &QuestionResult) {
+        let answered = u64::from(self.correct + self.incorrect);
+        let time_ms = result.time.as_millis() as u64;
+        self.avg_time_ms =
+            ((u64::from(self.avg_time_ms) * answered + time_ms) / (answered + 1)) as u32;
+        let correct = result.outcome == Outcome::Correct;
+        if correct {
+            self.correct += 1;
+        } else {
+            self.incorrect += 1;
+        }
+        self.recent.push(correct);
+        if self.recent.len() > MASTERY_WINDOW {
+            self.recent.remove(0);
+        }
+        self.text = result.question.text.clone();
+        self.category = result.question.category.clone();
+    }
+
+    /// How well the question is known, from 0 to 1.
+    ///
+    /// A weighted average of the recent results in which the newest result counts the most.
+    pub fn mastery(&self) -> f64 {
+        let mut total = 0.0;
+        let mut weights = 0.0;
+        for (age, correct) in self.recent.iter().rev().enumerate() {
+            let weight = 1.0 / (age as f64 + 1.0);
+            weights += weight;
+            if *correct {
+                total += weight;
+            }
+        }
+        if weights == 0.0 {
+            0.0
+        } else {
+            total / weights
+        }
+    }
+}
+
+fn stats_path() -> io::Result<PathBuf> {
+    app_dirs().profile_file(STATS_FILENAME)
+}
+
+fn load_stats() -> io::Result<HashMap<String, QuestionStats>> {
+    let contents = match fs::read_to_string(stats_path()?) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(err),
+    };
+    serde_json::from_str(&contents).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+}
+
+/// The mastery score of every question that has been answered before, by stats key.
+pub fn load_mastery() -> HashMap<String, f64> {
+    match load_stats() {
+        Ok(stats) => stats
+            .into_iter()
+            .map(|(key, stats)| (key, stats.mastery()))
+            .collect(),
+        Err(err) => {
+            println!("Warning: could not read the question stats: {err}");
+            HashMap::new()
+        }
+    }
+}
+
+/// Adds the answered questions of a session to the stored stats. Skipped questions are left out.
+pub fn update_question_stats(results: &[QuestionResult]) {
+    let result = load_stats().and_then(|mut stats| {
+        for result in results.iter().filter(|r| r.outcome != Outcome::Skipped) {
+            let key = result.question.stats_key();
+            //A question that got an id takes along the stats kept under its prompt
+            if !stats.contains_key(&key) {
+                if let Some(old) = stats.remove(&normalize_text(&result.question.text)) {
+                    stats.insert(key.clone(), old);
+                }
+            }
+            stats.entry(key).or_default().record(result);
+        }
+        let json = serde_json::to_string_pretty(&stats)
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+        fs::write(stats_path()?, json)
+    });
+    if let Err(err) = result {
+        println!("Warning: could not update the question stats: {err}");
+    }
+}
+
+/// How often questions of each category were answered wrong, the most missed first.
+/// Questions without a category are left out.
+pub fn misses_by_category() -> io::Result<Vec<(String, u32)>> {
+    let mut misses: HashMap<String, u32> = HashMap::new();
+    for stats in load_stats()?.into_values() {
+        if let (Some(category), incorrect @ 1..) = (stats.category, stats.incorrect) {
+            *misses.entry(category).or_default() += incorrect;
+        }
+    }
+    let mut misses: Vec<(String, u32)> = misses.into_iter().collect();
+    misses.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(misses)
+}
+
+/// Prints the questions that are most often answered wrong and the ones that take the longest.
+pub fn print_question_stats() {
+    let stats = match load_stats() {
+        Ok(stats) => stats,
+        Err(err) => {
+            println!("Could not read the question stats: {err}");
+            std::process::exit(1);
+        }
+    };
+    if stats.is_empty() {
+        println!("No questions have been answered yet.");
+        return;
+    }
+    let mut stats: Vec<QuestionStats> = stats.into_values().collect();
+
+    println!("Most often answered wrong:");
+    stats.sort_by(|a, b| b.incorrect.cmp(&a.incorrect));
+    for entry in stats.iter().filter(|s| s.incorrect > 0).take(REPORT_LENGTH) {
+        println!(
+            "{:>4} wrong, {:>4} right  {}",
+            entry.incorrect, entry.correct, entry.text
+        );
+    }
+
+    println!();
+    println!("Slowest to answer:");
+    stats.sort_by(|a, b| b.avg_time_ms.cmp(&a.avg_time_ms));
+    for entry in stats.iter().take(REPORT_LENGTH) {
+        println!(
+            "{:>7.1}s  {}",
+            f64::from(entry.avg_time_ms) / 1000.0,
+            entry.text
+        );
+    }
+}
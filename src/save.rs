@@ -0,0 +1,164 @@
+#[cfg(test)]
+use std::cell::RefCell;
+use std::fs;
+use std::io::{self, ErrorKind};
+#[cfg(test)]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dirs::app_dirs;
+use crate::question::Question;
+
+const SAVE_FILENAME: &str = "saved_game.json";
+
+/// The game as it was running, for the Ctrl-C handler to save.
+static TRACKED_GAME: Mutex<Option<GameState>> = Mutex::new(None);
+
+/// Everything needed to pick a game back up where it was left.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GameState {
+    pub source: String,
+    pub questions: Vec<Question>,
+    /// The question that is asked next.
+    pub index: usize,
+    pub correct: u32,
+    pub incorrect: u32,
+    pub partial: u32,
+    pub skipped: u32,
+    pub points: u32,
+    pub hints_used: u32,
+    pub bank: u32,
+    pub streak: u32,
+    /// The longest streak of correct answers so far.
+    #[serde(default)]
+    pub best_streak: u32,
+    /// The players taking turns in a hot-seat game, empty when playing alone.
+    #[serde(default)]
+    pub players: Vec<PlayerScore>,
+    /// The difficulty level adaptive mode aims for, 0 for easy up to 2 for hard.
+    #[serde(default)]
+    pub level: usize,
+    /// Whether the question asked next is a bonus question worth double points.
+    #[serde(default)]
+    pub bonus: bool,
+}
+
+/// The tallies of one player in a hot-seat game.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlayerScore {
+    pub name: String,
+    pub correct: u32,
+    pub incorrect: u32,
+    pub points: u32,
+}
+
+impl PlayerScore {
+    pub fn new(name: String) -> PlayerScore {
+        PlayerScore {
+            name,
+            correct: 0,
+            incorrect: 0,
+            points: 0,
+        }
+    }
+}
+
+impl GameState {
+    pub fn new(source: &str, questions: Vec<Question>) -> GameState {
+        GameState {
+            source: source.to_string(),
+            questions,
+            index: 0,
+            correct: 0,
+            incorrect: 0,
+            partial: 0,
+            skipped: 0,
+            points: 0,
+            hints_used: 0,
+            bank: 0,
+            streak: 0,
+            best_streak: 0,
+            players: Vec::new(),
+            level: 1,
+            bonus: false,
+        }
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Where the games of a test are saved instead of the profile. Tests run on threads of
+    /// their own, so they don't share it.
+    static TEST_SAVE_DIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// Saves the games played on this thread in the directory, keeping tests away from the
+/// saved game of whoever runs them.
+#[cfg(test)]
+pub fn set_test_save_dir(dir: &Path) {
+    TEST_SAVE_DIR.with(|cell| *cell.borrow_mut() = Some(dir.to_path_buf()));
+}
+
+fn save_path() -> io::Result<PathBuf> {
+    #[cfg(test)]
+    if let Some(dir) = TEST_SAVE_DIR.with(|cell| cell.borrow().clone()) {
+        return Ok(dir.join(SAVE_FILENAME));
+    }
+    app_dirs().profile_file(SAVE_FILENAME)
+}
+
+pub fn save_game(state: &GameState) -> io::Result<()> {
+    let json =
+        serde_json::to_string(state).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+    fs::write(save_path()?, json)
+}
+
+/// Loads the saved game, if there is one.
+pub fn load_saved_game() -> Option<GameState> {
+    let contents = fs::read_to_string(save_path().ok()?).ok()?;
+    match serde_json::from_str(&contents) {
+        Ok(state) => Some(state),
+        Err(err) => {
+            println!("Ignoring the saved game, it could not be read: {err}");
+            None
+        }
+    }
+}
+
+pub fn delete_saved_game() {
+    let result = save_path().and_then(fs::remove_file);
+    if let Err(err) = result {
+        if err.kind() != ErrorKind::NotFound {
+            println!("Warning: could not delete the saved game: {err}");
+        }
+    }
+}
+
+/// Keeps a copy of the running game so it can be saved when the quiz is interrupted.
+pub fn track_game(state: &GameState) {
+    if let Ok(mut tracked) = TRACKED_GAME.lock() {
+        *tracked = Some(state.clone());
+    }
+}
+
+pub fn untrack_game() {
+    if let Ok(mut tracked) = TRACKED_GAME.lock() {
+        *tracked = None;
+    }
+}
+
+/// Saves the running game, if any. Called when the quiz is interrupted.
+pub fn save_tracked_game() {
+    let Ok(tracked) = TRACKED_GAME.lock() else {
+        return;
+    };
+    if let Some(state) = tracked.as_ref() {
+        match save_game(state) {
+            Ok(()) => println!("\nGame saved. Start the quiz again to resume it."),
+            Err(err) => println!("\nCould not save the game: {err}"),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::path::Path;
+
+use serde::Serialize;
+use strsim::levenshtein;
+
+use crate::question::Question;
+use crate::source::get_questions_from_path;
+
+/// How many characters of the prompt and answer the table shows.
+const TEXT_WIDTH: usize = 50;
+const ANSWER_WIDTH: usize = 25;
+/// How many edits `--fuzzy` allows when `--max-distance` isn't given.
+pub const DEFAULT_MAX_DISTANCE: usize = 5;
+
+#[derive(Serialize)]
+struct SearchMatch<'a> {
+    /// The position of the question in the file, starting at 1.
+    index: usize,
+    #[serde(flatten)]
+    question: &'a Question,
+}
+
+/// Prints the questions of the file whose prompt or answer contains the keyword, ignoring case.
+///
+/// With `fuzzy` set, prompts that come close enough to the keyword match too, closest first.
+/// See `allowed_distance` for how close that is.
+pub fn run_search(keyword: &str, path: &Path, json: bool, fuzzy: bool, max_distance: usize) {
+    let questions = get_questions_from_path(path, false);
+    let keyword = keyword.to_lowercase();
+    let max_distance = allowed_distance(&keyword, max_distance);
+    let mut matches: Vec<(usize, SearchMatch)> = questions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, question)| {
+            let distance = if question.text.to_lowercase().contains(&keyword)
+                || question.answer.to_lowercase().contains(&keyword)
+            {
+                0
+            } else if fuzzy {
+                fuzzy_distance(&keyword, &question.text)
+            } else {
+                return None;
+            };
+            (distance <= max_distance || !fuzzy).then_some((
+                distance,
+                SearchMatch {
+                    index: index + 1,
+                    question,
+                },
+            ))
+        })
+        .collect();
+    matches.sort_by_key(|(distance, _)| *distance);
+    let matches: Vec<SearchMatch> = matches.into_iter().map(|(_, found)| found).collect();
+    print_matches(&matches, json);
+}
+
+/// How many edits a fuzzy match may be away from the keyword. A third of the keyword may be
+/// typos, up to `max_distance`, so a short keyword doesn't match every short word.
+fn allowed_distance(keyword: &str, max_distance: usize) -> usize {
+    max_distance.min(keyword.chars().count() / 3)
+}
+
+/// The fewest edits that turn the term into a run of as many words of the text, ignoring case
+/// and punctuation.
+fn fuzzy_distance(term: &str, text: &str) -> usize {
+    let term = term.to_lowercase();
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .collect();
+    let length = term.split_whitespace().count().max(1);
+    if words.len() <= length {
+        return levenshtein(&term, &words.join(" "));
+    }
+    words
+        .windows(length)
+        .map(|window| levenshtein(&term, &window.join(" ")))
+        .min()
+        .unwrap_or(usize::MAX)
+}
+
+fn print_matches(matches: &[SearchMatch], json: bool) {
+    if json {
+        match serde_json::to_string_pretty(matches) {
+            Ok(json) => println!("{json}"),
+            Err(err) => {
+                println!("Could not write the results as JSON: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if matches.is_empty() {
+        println!("No questions match.");
+        return;
+    }
+    println!(
+        "{:>5}  {:<TEXT_WIDTH$}  {:<ANSWER_WIDTH$}",
+        "#", "Question", "Answer"
+    );
+    for found in matches {
+        println!(
+            "{:>5}  {:<TEXT_WIDTH$}  {:<ANSWER_WIDTH$}",
+            found.index,
+            truncate(&found.question.text, TEXT_WIDTH),
+            truncate(&found.question.answer, ANSWER_WIDTH)
+        );
+    }
+    println!("{} matching questions.", matches.len());
+}
+
+/// Cuts the text down to `width` characters, ending it with … if anything was cut.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width - 1).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_search_tolerates_typos() {
+        let distance = fuzzy_distance("capitl", "What is the capital of France?");
+        assert!(distance <= 2, "distance was {distance}");
+    }
+
+    #[test]
+    fn fuzzy_search_rejects_unrelated_words() {
+        assert!(fuzzy_distance("capitl", "Who painted the Mona Lisa?") > 2);
+    }
+
+    #[test]
+    fn the_default_distance_keeps_out_unrelated_prompts() {
+        for (keyword, text) in [
+            ("capitl", "Who painted the Mona Lisa?"),
+            ("capitl", "Is a tomato a fruit?"),
+            ("dog", "What is the speed of light?"),
+        ] {
+            let allowed = allowed_distance(keyword, DEFAULT_MAX_DISTANCE);
+            assert!(
+                fuzzy_distance(keyword, text) > allowed,
+                "{keyword} matched {text}"
+            );
+        }
+        let allowed = allowed_distance("capitl", DEFAULT_MAX_DISTANCE);
+        assert!(fuzzy_distance("capitl", "What is the capital of France?") <= allowed);
+    }
+}
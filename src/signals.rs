@@ -0,0 +1,21 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use signal_hook::consts::SIGTERM;
+
+/// Set once SIGTERM arrives. It is checked between questions and while waiting for keys.
+static TERMINATE: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+/// Starts watching for SIGTERM, as sent by `kill`, systemd or `docker stop`.
+pub fn watch_sigterm() {
+    let flag = TERMINATE.get_or_init(|| Arc::new(AtomicBool::new(false)));
+    if let Err(err) = signal_hook::flag::register(SIGTERM, Arc::clone(flag)) {
+        println!("Warning: could not register a SIGTERM handler: {err}");
+    }
+}
+
+pub fn terminate_requested() -> bool {
+    TERMINATE
+        .get()
+        .is_some_and(|flag| flag.load(Ordering::Relaxed))
+}
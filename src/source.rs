@@ -0,0 +1,1775 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use clap::ValueEnum;
+use encoding_rs::{Encoding, UTF_8};
+use log::debug;
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::Proxy;
+use reqwest::StatusCode;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tempfile::NamedTempFile;
+use xml::attribute::OwnedAttribute;
+use xml::common::Position;
+use xml::name::OwnedName;
+use xml::reader::{ErrorKind, EventReader, Events, XmlEvent};
+use xml::writer::{EmitterConfig, XmlEvent as WriterEvent};
+use zip::ZipArchive;
+
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::dirs::app_dirs;
+use crate::error::QuizError;
+use crate::question::{Difficulty, Question};
+use crate::validate::remove_invalid;
+
+pub const API_URL: &str = "https://the-trivia-api.com/api/questions";
+const DEFAULT_API_LIMIT: usize = 10;
+/// The categories the trivia API knows, as its `categories` parameter names them.
+pub const API_CATEGORIES: &[&str] = &[
+    "arts_and_literature",
+    "film_and_tv",
+    "food_and_drink",
+    "general_knowledge",
+    "geography",
+    "history",
+    "music",
+    "science",
+    "society_and_culture",
+    "sport_and_leisure",
+];
+/// Seconds to wait for the API when neither the flag nor the config set it.
+pub const DEFAULT_API_TIMEOUT: u64 = 10;
+/// The exit code for a source that loaded fine but had no questions in it, or none the filters kept.
+pub const NO_QUESTIONS_EXIT_CODE: i32 = 2;
+const REQUEST_LOG_FILENAME: &str = "requests.log";
+/// How often a request is tried before a connection error is given up on.
+const API_ATTEMPTS: u32 = 3;
+/// The most questions the API hands out per request, unless `--api-page-size` says otherwise.
+pub const API_PAGE_SIZE: usize = 50;
+/// The wait between the rounds of requests for a larger limit, to stay clear of the rate limit.
+const API_PAGE_DELAY: Duration = Duration::from_millis(500);
+/// How many questions a lazy source requests at a time.
+const LAZY_BATCH_SIZE: usize = 10;
+/// The lazy source starts on the next batch once fewer questions than this are left.
+const LAZY_REFILL_BELOW: usize = 3;
+
+/// Where the questions of a game came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    File,
+    Web,
+    /// An Anki deck exported as `.apkg`.
+    Anki,
+    /// Questions piped into standard input.
+    Stdin,
+    /// Questions replayed from a wrong answer log.
+    #[value(skip)]
+    #[serde(skip)]
+    WrongLog,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::File => write!(f, "file"),
+            Source::Web => write!(f, "web"),
+            Source::Anki => write!(f, "anki"),
+            Source::Stdin => write!(f, "stdin"),
+            Source::WrongLog => write!(f, "wrong-log"),
+        }
+    }
+}
+
+/// How the questions piped into standard input are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum StdinFormat {
+    Json,
+    Xml,
+}
+
+/// The parameters of a question request to the trivia API.
+#[derive(Clone)]
+pub struct ApiQuery {
+    /// Where to request the questions from, normally `API_URL`.
+    pub base_url: String,
+    pub limit: Option<usize>,
+    pub difficulty: Option<Difficulty>,
+    /// One of `API_CATEGORIES`, or `None` for questions of any category.
+    pub category: Option<String>,
+    /// How many seconds to wait for the API before giving up.
+    pub timeout: u64,
+    /// A question file to play instead when the API can't be reached in time.
+    pub fallback_file: Option<PathBuf>,
+    /// The proxy to send the request through, instead of the one from the environment.
+    pub proxy: Option<String>,
+    /// Write the raw response to the request log.
+    pub debug_requests: bool,
+    /// How many requests may be underway at once when the limit needs several.
+    pub parallel: usize,
+    /// The most questions asked for in a single request.
+    pub page_size: usize,
+    /// Seconds a cached response is played again instead of asking the API, `None` to skip
+    /// the cache.
+    pub cache_ttl: Option<u64>,
+    /// Print every progress update on a line of its own instead of redrawing one line.
+    pub plain: bool,
+}
+
+impl ApiQuery {
+    fn url(&self, limit: usize) -> String {
+        let mut url = format!("{}?limit={limit}", self.base_url);
+        if let Some(category) = &self.category {
+            url.push_str(&format!("&categories={category}"));
+        }
+        if let Some(difficulty) = self.difficulty {
+            url.push_str(&format!("&difficulty={difficulty}"));
+        }
+        url
+    }
+
+    /// The URL of the whole request, for showing it. Larger limits are split up when sent.
+    pub fn full_url(&self) -> String {
+        self.url(self.wanted())
+    }
+
+    /// How many questions are asked for.
+    pub fn wanted(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_API_LIMIT)
+    }
+}
+
+pub fn get_questions_from_api(config: &Config, query: &ApiQuery) -> Vec<Question> {
+    match fetch_questions(config, query) {
+        Ok(questions) if questions.is_empty() => {
+            println!("The trivia API returned no questions. Try a different difficulty or limit.");
+            std::process::exit(NO_QUESTIONS_EXIT_CODE)
+        }
+        Ok(questions) => questions,
+        Err(QuizError::Http(err)) if err.is_timeout() => timed_out(query),
+        Err(err) => {
+            println!("{err}");
+            std::process::exit(1)
+        }
+    }
+}
+
+/// Requests the questions from the API, or takes them from the cache when the same request was
+/// answered within the cache TTL.
+pub fn fetch_questions(config: &Config, query: &ApiQuery) -> Result<Vec<Question>, QuizError> {
+    let wanted = query.wanted();
+    let key = query.url(wanted);
+    let cache = query
+        .cache_ttl
+        .and_then(|ttl| match Cache::new(Duration::from_secs(ttl)) {
+            Ok(cache) => Some(cache),
+            Err(err) => {
+                println!("Warning: could not open the cache: {err}");
+                None
+            }
+        });
+    if let Some(questions) = cache.as_ref().and_then(|cache| cache.get(&key)) {
+        return Ok(questions);
+    }
+    let questions = fetch_pages(config, query, wanted)?;
+    if let Some(cache) = &cache {
+        if let Err(err) = cache.set(&key, &questions) {
+            println!("Warning: could not write to the cache: {err}");
+        }
+    }
+    Ok(questions)
+}
+
+/// Requests the questions in several requests if there are more than the API hands out at once.
+/// Up to `query.parallel` of them are sent together.
+fn fetch_pages(
+    config: &Config,
+    query: &ApiQuery,
+    wanted: usize,
+) -> Result<Vec<Question>, QuizError> {
+    let client = build_client(query.timeout, query.proxy.as_deref());
+    let page_size = query.page_size.max(1);
+    let paged = wanted > page_size;
+    let mut seen = HashSet::new();
+    let mut questions = Vec::new();
+    let mut requests = 0;
+    while questions.len() < wanted {
+        if requests > 0 {
+            thread::sleep(API_PAGE_DELAY);
+        }
+        if paged {
+            print!("{}", progress_line(questions.len(), wanted, query.plain));
+            let _ = io::stdout().flush();
+        }
+        let missing = wanted - questions.len();
+        let batch = missing.div_ceil(page_size).min(query.parallel.max(1));
+        let urls: Vec<String> = (0..batch)
+            .map(|page| query.url((missing - page * page_size).min(page_size)))
+            .collect();
+        requests += urls.len();
+        let client = &client;
+        let pages = thread::scope(|scope| {
+            let handles: Vec<_> = urls
+                .iter()
+                .map(|url| scope.spawn(move || timed_fetch_page(client, config, query, url)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("an API request panicked"))
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+        let before = questions.len();
+        //The pages are drawn at random, so later ones can repeat earlier questions
+        questions.extend(
+            pages
+                .into_iter()
+                .flatten()
+                .filter(|question| seen.insert(question.stats_key())),
+        );
+        if questions.len() == before {
+            break;
+        }
+    }
+    if paged {
+        print!("{}", progress_line(questions.len(), wanted, query.plain));
+        if !query.plain {
+            println!();
+        }
+    }
+    if requests > 1 {
+        println!(
+            "Gathered {} unique questions in {requests} requests.",
+            questions.len()
+        );
+    }
+    Ok(questions)
+}
+
+/// The progress of loading the questions, written over the previous one unless `plain`.
+fn progress_line(loaded: usize, wanted: usize, plain: bool) -> String {
+    let line = format!("Loading questions... ({loaded}/{wanted})");
+    if plain {
+        format!("{line}\n")
+    } else {
+        format!("\r{line}")
+    }
+}
+
+/// `fetch_page`, logging how long the request took.
+fn timed_fetch_page(
+    client: &Client,
+    config: &Config,
+    query: &ApiQuery,
+    url: &str,
+) -> Result<Vec<Question>, QuizError> {
+    let started = Instant::now();
+    let result = fetch_page(client, config, query, url);
+    debug!(
+        "Request to {url} took {:.0}ms",
+        started.elapsed().as_secs_f64() * 1000.0
+    );
+    result
+}
+
+/// Hands out questions one at a time while a game is being played.
+pub trait QuestionSource {
+    fn next_question(&mut self) -> Option<Question>;
+}
+
+/// Fetches questions from the API in batches as the game goes on, so it never runs out.
+///
+/// The next batch is requested on a background thread while the current questions are played.
+pub struct LazyApiSource {
+    url: String,
+    /// How many batches have been requested.
+    page: usize,
+    buffer: VecDeque<Question>,
+    seen: HashSet<String>,
+    pending: Option<Receiver<Result<Vec<Question>, QuizError>>>,
+    exhausted: bool,
+    client: Client,
+    config: Arc<Config>,
+    query: Arc<ApiQuery>,
+}
+
+impl LazyApiSource {
+    /// Starts on the first batch right away.
+    pub fn new(config: &Config, query: ApiQuery) -> LazyApiSource {
+        let mut source = LazyApiSource {
+            url: query.url(LAZY_BATCH_SIZE),
+            page: 0,
+            buffer: VecDeque::new(),
+            seen: HashSet::new(),
+            pending: None,
+            exhausted: false,
+            client: build_client(query.timeout, query.proxy.as_deref()),
+            config: Arc::new(config.clone()),
+            query: Arc::new(query),
+        };
+        source.pending = Some(source.request_batch());
+        source
+    }
+
+    fn request_batch(&mut self) -> Receiver<Result<Vec<Question>, QuizError>> {
+        let (sender, receiver) = mpsc::channel();
+        let client = self.client.clone();
+        let config = Arc::clone(&self.config);
+        let query = Arc::clone(&self.query);
+        let url = self.url.clone();
+        self.page += 1;
+        debug!("Prefetching batch {} of questions", self.page);
+        thread::spawn(move || {
+            //The game may have ended before the batch arrives, nobody is listening then
+            let _ = sender.send(fetch_page(&client, &config, &query, &url));
+        });
+        receiver
+    }
+
+    /// Waits for the pending batch and adds its new questions to the buffer. A batch without
+    /// any, or one that failed, ends the supply.
+    fn receive(&mut self) {
+        let receiver = self.pending.take().unwrap_or_else(|| self.request_batch());
+        let result = match receiver.try_recv() {
+            Ok(result) => Ok(result),
+            Err(TryRecvError::Empty) => {
+                println!("Loading more questions...");
+                receiver.recv()
+            }
+            Err(TryRecvError::Disconnected) => receiver.recv(),
+        };
+        match result {
+            Ok(Ok(page)) => {
+                let before = self.buffer.len();
+                let seen = &mut self.seen;
+                self.buffer
+                    .extend(page.into_iter().filter(|question| match &question.id {
+                        Some(id) => seen.insert(id.clone()),
+                        None => true,
+                    }));
+                if self.buffer.len() == before {
+                    println!("The API has no new questions left.");
+                    self.exhausted = true;
+                }
+            }
+            Ok(Err(err)) => {
+                println!("Could not load more questions: {err}");
+                self.exhausted = true;
+            }
+            Err(_) => self.exhausted = true,
+        }
+    }
+}
+
+impl QuestionSource for LazyApiSource {
+    fn next_question(&mut self) -> Option<Question> {
+        while self.buffer.is_empty() && !self.exhausted {
+            self.receive();
+        }
+        let question = self.buffer.pop_front();
+        if self.buffer.len() < LAZY_REFILL_BELOW && self.pending.is_none() && !self.exhausted {
+            self.pending = Some(self.request_batch());
+        }
+        question
+    }
+}
+
+/// Requests one batch of questions, trying again when the connection fails or the server has
+/// an error.
+fn fetch_page(
+    client: &Client,
+    config: &Config,
+    query: &ApiQuery,
+    url: &str,
+) -> Result<Vec<Question>, QuizError> {
+    let mut attempt = 1;
+    let res = loop {
+        debug!("Requesting {url}");
+        let res = api_request(client, url, config)
+            .send()
+            .and_then(|res| res.error_for_status());
+        match res {
+            Ok(res) => break res,
+            //Flaky connections and overloaded servers often work out on the next try
+            Err(err) if is_transient(&err) && attempt < API_ATTEMPTS => {
+                attempt += 1;
+                debug!("Request failed ({err}), retrying, attempt {attempt} of {API_ATTEMPTS}");
+            }
+            Err(err) => return Err(err.into()),
+        }
+    };
+    let body = res.text()?;
+    if query.debug_requests {
+        log_response(url, &body);
+    }
+    let questions = parse_api_response(&body).map_err(QuizError::Api)?;
+    log_parsed(&questions, "the API");
+    Ok(questions)
+}
+
+fn is_transient(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.status().is_some_and(|status| status.is_server_error())
+}
+
+/// Reads the questions out of the API's JSON, naming the question and field that don't fit.
+fn parse_api_response(body: &str) -> Result<Vec<Question>, String> {
+    let value: Value = serde_json::from_str(body)
+        .map_err(|err| format!("The API response is not valid JSON: {err}"))?;
+    let Value::Array(items) = value else {
+        return Err("The API response is not a list of questions.".to_string());
+    };
+    let mut questions = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        let number = index + 1;
+        let Some(object) = item.as_object() else {
+            return Err(format!("API response question {number} is not an object."));
+        };
+        for (field, alias, expected) in API_FIELDS {
+            match object.get(*field).or_else(|| object.get(*alias)) {
+                None => {
+                    return Err(format!(
+                        "API response question {number} is missing field `{field}`"
+                    ))
+                }
+                Some(value) if !expected.matches(value) => {
+                    return Err(format!(
+                        "API response question {number} has field `{field}` of the wrong type, expected {}",
+                        expected.name()
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+        match serde_json::from_value(item) {
+            Ok(question) => questions.push(question),
+            Err(err) => return Err(format!("API response question {number} is invalid: {err}")),
+        }
+    }
+    Ok(questions)
+}
+
+/// The JSON type a field of an API question has to have.
+enum FieldType {
+    Text,
+    TextList,
+}
+
+impl FieldType {
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            FieldType::Text => value.is_string(),
+            FieldType::TextList => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(Value::is_string)),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            FieldType::Text => "a string",
+            FieldType::TextList => "a list of strings",
+        }
+    }
+}
+
+/// The fields every API question needs, as named by the API and by our own JSON files.
+const API_FIELDS: &[(&str, &str, FieldType)] = &[
+    ("question", "text", FieldType::Text),
+    ("correctAnswer", "answer", FieldType::Text),
+    ("incorrectAnswers", "wrong_answers", FieldType::TextList),
+];
+
+/// Appends the raw response to the request log in the data directory, for `--debug-requests`.
+fn log_response(url: &str, body: &str) {
+    let result = app_dirs().data_dir().and_then(|dir| {
+        let path = dir.join(REQUEST_LOG_FILENAME);
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{} GET {url}", Local::now().to_rfc3339())?;
+        writeln!(file, "{body}")?;
+        Ok(path)
+    });
+    match result {
+        Ok(path) => println!("Logged the API response to {}", path.display()),
+        Err(err) => println!("Warning: could not write the request log: {err}"),
+    }
+}
+
+/// Builds the HTTP client for the API.
+///
+/// Without an explicit proxy reqwest picks up the `HTTP_PROXY` and `HTTPS_PROXY` environment variables.
+fn build_client(timeout: u64, proxy: Option<&str>) -> Client {
+    let mut builder = Client::builder().timeout(Duration::from_secs(timeout));
+    if let Some(url) = proxy {
+        match Proxy::all(url) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(err) => {
+                println!("Invalid proxy {url}: {err}");
+                std::process::exit(1)
+            }
+        }
+    }
+    match builder.build() {
+        Ok(client) => client,
+        Err(err) => {
+            println!("Could not set up the HTTP client: {err}");
+            std::process::exit(1)
+        }
+    }
+}
+
+/// Falls back to the fallback file if there is one, exits otherwise.
+fn timed_out(query: &ApiQuery) -> Vec<Question> {
+    println!(
+        "Request timed out after {} seconds. Check your internet connection.",
+        query.timeout
+    );
+    match &query.fallback_file {
+        Some(path) => {
+            println!("Playing the questions from {} instead.", path.display());
+            get_questions_from_path(path, false)
+        }
+        None => std::process::exit(1),
+    }
+}
+
+/// Loads the questions file and leaves out incomplete questions, or exits on them when `strict`.
+///
+/// With `max` only that many questions are read from an XML file, the rest of it is never parsed.
+pub fn get_questions_from_file(
+    skip_invalid: bool,
+    strict: bool,
+    max: Option<usize>,
+) -> Vec<Question> {
+    let path = question_file_path();
+    let questions = match max {
+        Some(max) if FileFormat::of(&path) == FileFormat::Xml => {
+            read_first_questions(&path, max, skip_invalid)
+        }
+        _ => get_questions_from_path(&path, skip_invalid),
+    };
+    let questions = remove_invalid(questions, strict);
+    if questions.is_empty() {
+        println!(
+            "No questions were found in `{}`. Is the file empty or does it use a different XML schema?",
+            path.display()
+        );
+        std::process::exit(NO_QUESTIONS_EXIT_CODE);
+    }
+    questions
+}
+
+/// Streams the first `max` questions out of an XML file, without reading the rest of it.
+///
+/// Only UTF-8 files are streamed, others are transcoded and loaded whole. So are files when
+/// `skip_invalid` is set, as skipping a broken question needs all of them. The questions of
+/// included files come after those of the file itself, so they are only loaded when the file
+/// runs out first.
+fn read_first_questions(path: &Path, max: usize, skip_invalid: bool) -> Vec<Question> {
+    let Ok(file) = File::open(path) else {
+        return get_questions_from_path(path, skip_invalid);
+    };
+    let mut reader = BufReader::new(file);
+    let start = match reader.fill_buf() {
+        Ok(start) => start,
+        Err(_) => return get_questions_from_path(path, skip_invalid),
+    };
+    let bom = Encoding::for_bom(start);
+    let encoding = match bom {
+        Some((encoding, _)) => Some(encoding),
+        None => match declared_encoding(start) {
+            Some(label) => Encoding::for_label(label.as_bytes()),
+            None => Some(UTF_8),
+        },
+    };
+    if skip_invalid || encoding != Some(UTF_8) {
+        return get_questions_from_path(path, skip_invalid);
+    }
+    if let Some((_, length)) = bom {
+        reader.consume(length);
+    }
+
+    let mut includes = Vec::new();
+    let streamed: Result<Vec<Question>, _> =
+        parse_data_streaming(EventReader::new(reader), max, &mut includes).collect();
+    let mut questions = match streamed {
+        Ok(questions) => questions,
+        Err(err) => {
+            print_xml_error(path, &err, 0);
+            std::process::exit(1);
+        }
+    };
+    if questions.len() < max && !includes.is_empty() {
+        let mut parsing =
+            HashSet::from([fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())]);
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for src in includes {
+            let included = dir.join(&src);
+            if !included.is_file() {
+                println!(
+                    "Could not find {}, included by {}.",
+                    included.display(),
+                    path.display()
+                );
+                std::process::exit(1);
+            }
+            match load_xml_with_includes(&included, false, &mut parsing) {
+                Ok(more) => questions.extend(more),
+                Err(message) => {
+                    println!("{message}");
+                    std::process::exit(1);
+                }
+            }
+            if questions.len() >= max {
+                break;
+            }
+        }
+        questions.truncate(max);
+    }
+    log_parsed(&questions, &path.display().to_string());
+    questions
+}
+
+/// The formats question files can be written in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FileFormat {
+    Xml,
+    Json,
+    Toml,
+}
+
+impl FileFormat {
+    /// Picks the format by the extension. Anything but `.json` and `.toml` is XML.
+    fn of(path: &Path) -> FileFormat {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("json") => FileFormat::Json,
+            Some("toml") => FileFormat::Toml,
+            _ => FileFormat::Xml,
+        }
+    }
+}
+
+/// The layout of a TOML question file, a `[[questions]]` table for each question.
+#[derive(Deserialize, Serialize)]
+struct TomlQuestions {
+    questions: Vec<Question>,
+}
+
+/// Loads questions from a file, picking the format by its extension.
+///
+/// Malformed XML ends the program, unless `skip_invalid` is set. Then the questions are parsed
+/// one at a time and the broken ones are left out.
+pub fn get_questions_from_path(path: &Path, skip_invalid: bool) -> Vec<Question> {
+    let questions = match FileFormat::of(path) {
+        FileFormat::Json => load_json_file(path),
+        FileFormat::Toml => load_toml_file(path),
+        FileFormat::Xml => load_xml_file(path, skip_invalid),
+    };
+    log_parsed(&questions, &path.display().to_string());
+    questions
+}
+
+fn load_xml_file(path: &Path, skip_invalid: bool) -> Vec<Question> {
+    match load_xml_with_includes(path, skip_invalid, &mut HashSet::new()) {
+        Ok(questions) => questions,
+        Err(message) => {
+            println!("{message}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Loads an XML question file followed by the files it includes with `<include src="..."/>`.
+///
+/// `parsing` holds the files whose includes are being followed, to catch a file that ends up
+/// including itself.
+fn load_xml_with_includes(
+    path: &Path,
+    skip_invalid: bool,
+    parsing: &mut HashSet<PathBuf>,
+) -> Result<Vec<Question>, String> {
+    let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if !parsing.insert(key.clone()) {
+        return Err(format!(
+            "{} is included in a circle, it ends up including itself.",
+            path.display()
+        ));
+    }
+    let mut includes = Vec::new();
+    let mut questions = match parse_data_with_includes(load_file(path), &mut includes) {
+        Ok(questions) => questions,
+        Err(err) => {
+            print_xml_error(path, &err, 0);
+            if !skip_invalid {
+                std::process::exit(1);
+            }
+            parse_questions_separately(path)
+        }
+    };
+    //Includes are relative to the file that has them
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for src in includes {
+        let included = dir.join(&src);
+        if !included.is_file() {
+            return Err(format!(
+                "Could not find {}, included by {}.",
+                included.display(),
+                path.display()
+            ));
+        }
+        questions.extend(load_xml_with_includes(&included, skip_invalid, parsing)?);
+    }
+    parsing.remove(&key);
+    Ok(questions)
+}
+
+/// Parses every `<question>` element of the file on its own, skipping those that are malformed.
+fn parse_questions_separately(path: &Path) -> Vec<Question> {
+    let contents = read_xml_file(path);
+    let mut questions = Vec::new();
+    for (start, chunk) in question_chunks(&contents) {
+        let line = contents[..start].matches('\n').count() as u64;
+        match parse_data(EventReader::new(chunk.as_bytes())) {
+            Ok(parsed) => questions.extend(parsed),
+            Err(err) => {
+                print_xml_error(path, &err, line);
+                println!("Skipping the question starting on line {}.", line + 1);
+            }
+        }
+    }
+    questions
+}
+
+/// Splits the text into the `<question>` elements it contains, with their byte offset.
+///
+/// A question that is never closed runs until the next one starts.
+fn question_chunks(contents: &str) -> Vec<(usize, &str)> {
+    let is_start = |rest: &str| {
+        rest.starts_with("<question")
+            && rest["<question".len()..].starts_with(|c: char| c == '>' || c.is_whitespace())
+    };
+    let starts: Vec<usize> = contents
+        .match_indices("<question")
+        .map(|(index, _)| index)
+        .filter(|index| is_start(&contents[*index..]))
+        .collect();
+    let mut chunks = Vec::new();
+    for (i, start) in starts.iter().enumerate() {
+        let limit = starts.get(i + 1).copied().unwrap_or(contents.len());
+        let end = match contents[*start..limit].find("</question>") {
+            Some(end) => start + end + "</question>".len(),
+            None => limit,
+        };
+        chunks.push((*start, &contents[*start..end]));
+    }
+    chunks
+}
+
+/// Prints an XML error as `file:line:column: message`, with a hint on how to fix it.
+///
+/// `line_offset` is added to the line, for errors in a part of the file that was parsed on its own.
+fn print_xml_error(path: &Path, err: &xml::reader::Error, line_offset: u64) {
+    let position = err.position();
+    println!(
+        "{}:{}:{}: {}",
+        path.display(),
+        position.row + line_offset + 1,
+        position.column + 1,
+        err.msg().to_lowercase()
+    );
+    if let Some(suggestion) = suggest_fix(err) {
+        println!("{suggestion}");
+    }
+}
+
+fn suggest_fix(err: &xml::reader::Error) -> Option<String> {
+    let msg = err.msg();
+    match err.kind() {
+        ErrorKind::Utf8(_) => Some("Is the file saved as UTF-8?".to_string()),
+        ErrorKind::UnexpectedEof => {
+            Some("Did you forget to close a tag before the end of the file?".to_string())
+        }
+        _ if msg.starts_with("Unexpected closing tag") => match msg.rsplit_once("expected ") {
+            Some((_, expected)) => Some(format!("Did you forget to close the <{expected}> tag?")),
+            None => Some("Check that every tag is closed in the right order.".to_string()),
+        },
+        _ if msg.starts_with("Unexpected end of stream") => {
+            Some("Did you forget to close a tag before the end of the file?".to_string())
+        }
+        _ if msg.contains("entity") => Some("Write a literal & as &amp;.".to_string()),
+        _ if msg.contains("'<'") => Some("Write a literal < as &lt;.".to_string()),
+        _ => None,
+    }
+}
+
+/// Reads questions from standard input until it is closed.
+pub fn get_questions_from_stdin(format: StdinFormat) -> Vec<Question> {
+    let stdin = io::stdin().lock();
+    let questions = match format {
+        StdinFormat::Json => match serde_json::from_reader(stdin) {
+            Ok(questions) => questions,
+            Err(err) => {
+                println!("Error in the questions from stdin: {err}");
+                std::process::exit(1);
+            }
+        },
+        StdinFormat::Xml => match parse_data(EventReader::new(stdin)) {
+            Ok(questions) => questions,
+            Err(err) => {
+                print_xml_error(Path::new("<stdin>"), &err, 0);
+                std::process::exit(1);
+            }
+        },
+    };
+    log_parsed(&questions, "stdin");
+    if questions.is_empty() {
+        println!("No questions were piped into stdin.");
+        std::process::exit(NO_QUESTIONS_EXIT_CODE);
+    }
+    questions
+}
+
+/// Reads the notes of an Anki deck as questions.
+///
+/// The first field of a note is the prompt, the second the answer and any further ones are wrong answers.
+pub fn get_questions_from_anki(path: &Path) -> Result<Vec<Question>, QuizError> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    //Newer Anki versions name the database collection.anki21
+    let name = ["collection.anki21", "collection.anki2"]
+        .into_iter()
+        .find(|name| archive.by_name(name).is_ok())
+        .ok_or_else(|| QuizError::Anki("the deck has no collection database".to_string()))?;
+    //SQLite wants a file, so unpack the database to a temporary one that is removed on drop
+    let mut database = NamedTempFile::new()?;
+    io::copy(&mut archive.by_name(name)?, &mut database)?;
+    let notes = read_anki_notes(database.path());
+
+    let mut questions = Vec::new();
+    for (index, note) in notes?.into_iter().enumerate() {
+        //Anki separates the fields of a note with the unit separator
+        let mut fields = note.split('\x1f').map(|field| field.trim().to_string());
+        let (Some(text), Some(answer)) = (fields.next(), fields.next()) else {
+            println!(
+                "Warning: note {} of {} has fewer than two fields, skipping it.",
+                index + 1,
+                path.display()
+            );
+            continue;
+        };
+        let mut question = Question::new();
+        question.text = text;
+        question.answer = answer;
+        question.wrong_answers = fields.filter(|field| !field.is_empty()).collect();
+        questions.push(question);
+    }
+    Ok(questions)
+}
+
+fn read_anki_notes(database: &Path) -> Result<Vec<String>, QuizError> {
+    let connection = Connection::open(database)?;
+    let mut statement = connection.prepare("SELECT flds FROM notes ORDER BY id")?;
+    let notes = statement
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(notes)
+}
+
+/// Writes questions to a file, in the format its extension asks for.
+pub fn save_questions_to_path(path: &Path, questions: &[Question]) -> io::Result<()> {
+    match FileFormat::of(path) {
+        FileFormat::Json => {
+            let file = BufWriter::new(File::create(path)?);
+            serde_json::to_writer_pretty(file, questions).map_err(io::Error::from)
+        }
+        FileFormat::Toml => {
+            let toml = toml::to_string_pretty(&TomlQuestions {
+                questions: questions.to_vec(),
+            })
+            .map_err(io::Error::other)?;
+            fs::write(path, toml)
+        }
+        FileFormat::Xml => {
+            let file = BufWriter::new(File::create(path)?);
+            serialize_questions_to_xml(questions, file).map_err(io::Error::other)
+        }
+    }
+}
+
+/// Writes the questions as XML in the format `parse_data` reads, so they can be loaded again.
+pub fn serialize_questions_to_xml(
+    questions: &[Question],
+    writer: impl Write,
+) -> Result<(), QuizError> {
+    let mut writer = EmitterConfig::new()
+        .perform_indent(true)
+        .create_writer(writer);
+    writer.write(WriterEvent::start_element("questions"))?;
+    for question in questions {
+        let mut start = WriterEvent::start_element("question");
+        if let Some(id) = &question.id {
+            start = start.attr("id", id);
+        }
+        if !question.shuffle {
+            start = start.attr("shuffle", "false");
+        }
+        writer.write(start)?;
+        let difficulty = question.difficulty.map(|d| d.to_string());
+        let fields = std::iter::once(("prompt", question.text.as_str()))
+            .chain(
+                question
+                    .correct_answers()
+                    .into_iter()
+                    .map(|answer| ("correctAnswer", answer)),
+            )
+            .chain(
+                question
+                    .wrong_answers
+                    .iter()
+                    .map(|answer| ("incorrectAnswer", answer.as_str())),
+            )
+            .chain(
+                question
+                    .acceptable_answers
+                    .iter()
+                    .map(|answer| ("acceptableAnswer", answer.as_str())),
+            )
+            .chain(
+                question
+                    .explanation
+                    .as_deref()
+                    .map(|explanation| ("explanation", explanation)),
+            )
+            .chain(question.source.as_deref().map(|source| ("source", source)))
+            .chain(
+                question
+                    .category
+                    .as_deref()
+                    .map(|category| ("category", category)),
+            )
+            .chain(difficulty.as_deref().map(|d| ("difficulty", d)))
+            .chain(question.tags.iter().map(|tag| ("tag", tag.as_str())));
+        for (name, text) in fields {
+            writer.write(WriterEvent::start_element(name))?;
+            writer.write(WriterEvent::characters(text))?;
+            writer.write(WriterEvent::end_element())?;
+        }
+        writer.write(WriterEvent::end_element())?;
+    }
+    writer.write(WriterEvent::end_element())?;
+    Ok(())
+}
+
+fn log_parsed(questions: &[Question], origin: &str) {
+    for question in questions {
+        debug!("Parsed question {} from {origin}", question.label());
+    }
+}
+
+fn load_json_file(path: &Path) -> Vec<Question> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => {
+            print!("{} not found. Exiting.", path.display());
+            std::process::exit(1);
+        }
+    };
+    match serde_json::from_reader(BufReader::new(file)) {
+        Ok(questions) => questions,
+        Err(err) => {
+            println!("Error in {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+fn load_toml_file(path: &Path) -> Vec<Question> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => {
+            print!("{} not found. Exiting.", path.display());
+            std::process::exit(1);
+        }
+    };
+    match toml::from_str::<TomlQuestions>(&contents) {
+        Ok(file) => file.questions,
+        Err(err) => {
+            println!("Error in {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+fn load_file(path: &Path) -> EventReader<Cursor<String>> {
+    EventReader::new(Cursor::new(read_xml_file(path)))
+}
+
+/// Reads an XML file as UTF-8 text, without a byte order mark and transcoded from the
+/// encoding the mark or else the declaration names.
+fn read_xml_file(path: &Path) -> String {
+    //Loading the file
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => {
+            print!("{} not found. Exiting.", path.display());
+            std::process::exit(1);
+        }
+    };
+    let (bytes, bom_encoding) = match read_without_bom(BufReader::new(file)) {
+        Ok(read) => read,
+        Err(err) => {
+            println!("Could not read {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    };
+
+    //A byte order mark, which Windows editors like to write, beats the declaration
+    let encoding = match (bom_encoding, declared_encoding(&bytes)) {
+        (Some(encoding), _) => encoding,
+        (None, Some(label)) => match Encoding::for_label(label.as_bytes()) {
+            Some(encoding) => encoding,
+            None => {
+                println!(
+                    "{} declares the unknown encoding \"{label}\".",
+                    path.display()
+                );
+                std::process::exit(1);
+            }
+        },
+        (None, None) => UTF_8,
+    };
+    if encoding == UTF_8 {
+        return match String::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(err) => {
+                println!(
+                    "{} is not valid UTF-8: {err}. Declare its encoding in the XML declaration, \
+                     like <?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>.",
+                    path.display()
+                );
+                std::process::exit(1);
+            }
+        };
+    }
+    let (text, had_errors) = encoding.decode_without_bom_handling(&bytes);
+    if had_errors {
+        println!(
+            "Warning: {} contains characters that are not valid {}.",
+            path.display(),
+            encoding.name()
+        );
+    }
+    //The text is UTF-8 now, so the declaration has to say so for the parser
+    match declared_encoding(text.as_bytes()) {
+        Some(label) => text.replacen(&label, "UTF-8", 1),
+        None => text.into_owned(),
+    }
+}
+
+/// Reads the file and takes off its byte order mark, returning the encoding the mark stands for.
+fn read_without_bom(mut file: BufReader<File>) -> io::Result<(Vec<u8>, Option<&'static Encoding>)> {
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    match Encoding::for_bom(&bytes) {
+        Some((encoding, length)) => Ok((bytes.split_off(length), Some(encoding))),
+        None => Ok((bytes, None)),
+    }
+}
+
+/// The encoding named in the XML declaration at the start of the file, if there is one.
+fn declared_encoding(bytes: &[u8]) -> Option<String> {
+    if !bytes.starts_with(b"<?xml") {
+        return None;
+    }
+    let end = bytes.windows(2).position(|pair| pair == b"?>")?;
+    //The declaration itself is plain ASCII in every encoding we can read
+    let declaration = String::from_utf8_lossy(&bytes[..end]);
+    let rest = &declaration[declaration.find("encoding")? + "encoding".len()..];
+    let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+    let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &rest[1..];
+    Some(value[..value.find(quote)?].to_string())
+}
+
+/// Prefers a questions file in the working directory and falls back to the data directory.
+pub fn question_file_path() -> PathBuf {
+    const FILENAME: &str = "questions.xml";
+    let local = PathBuf::from(FILENAME);
+    if local.exists() {
+        return local;
+    }
+    match app_dirs().data_dir() {
+        Ok(dir) => dir.join(FILENAME),
+        Err(err) => {
+            println!("Could not create the data directory: {err}");
+            local
+        }
+    }
+}
+
+/// Reads the questions out of XML, printing a warning for everything that doesn't belong.
+pub fn parse_data<R: Read>(parser: EventReader<R>) -> Result<Vec<Question>, xml::reader::Error> {
+    let mut includes = Vec::new();
+    let result = parse_data_with_includes(parser, &mut includes);
+    for src in includes {
+        println!("Ignoring the include of {src}, only question files can include others.");
+    }
+    result
+}
+
+/// Like `parse_data`, but hands the `src` of every `<include>` to the caller instead of
+/// ignoring it.
+fn parse_data_with_includes<R: Read>(
+    parser: EventReader<R>,
+    includes: &mut Vec<String>,
+) -> Result<Vec<Question>, xml::reader::Error> {
+    let mut warnings = Vec::new();
+    let result = parse_events(parser, &mut warnings, includes);
+    for warning in warnings {
+        println!("{warning}");
+    }
+    result
+}
+
+/// Does the work of `parse_data`, collecting the warnings about misplaced content instead of
+/// printing them.
+fn parse_events<R: Read>(
+    parser: EventReader<R>,
+    warnings: &mut Vec<String>,
+    includes: &mut Vec<String>,
+) -> Result<Vec<Question>, xml::reader::Error> {
+    let mut reader = QuestionReader::new(parser);
+    let result = reader.by_ref().collect();
+    warnings.append(&mut reader.warnings);
+    includes.append(&mut reader.includes);
+    result
+}
+
+/// Reads only the first `max` questions, one at a time, so the rest of the XML is never parsed.
+/// Warnings are printed as they come up, the `src` of every `<include>` is added to `includes`.
+pub fn parse_data_streaming<'a, R: Read + 'a>(
+    parser: EventReader<R>,
+    max: usize,
+    includes: &'a mut Vec<String>,
+) -> impl Iterator<Item = Result<Question, xml::reader::Error>> + 'a {
+    let mut reader = QuestionReader::new(parser);
+    std::iter::from_fn(move || {
+        let next = reader.next();
+        for warning in reader.warnings.drain(..) {
+            println!("{warning}");
+        }
+        includes.append(&mut reader.includes);
+        next
+    })
+    .take(max)
+}
+
+/// Reads the questions out of XML events one at a time, noting warnings and includes on the way.
+struct QuestionReader<R: Read> {
+    events: Events<R>,
+    cur_question: Option<Question>,
+    cur_data: Option<String>,
+    //Fields count if they are in the namespace of their question, or in none
+    question_namespace: Option<String>,
+    warnings: Vec<String>,
+    includes: Vec<String>,
+}
+
+impl<R: Read> QuestionReader<R> {
+    fn new(parser: EventReader<R>) -> Self {
+        QuestionReader {
+            events: parser.into_iter(),
+            cur_question: None,
+            cur_data: None,
+            question_namespace: None,
+            warnings: Vec::new(),
+            includes: Vec::new(),
+        }
+    }
+
+    /// Takes in one event, returning the question it completes if it does.
+    fn handle(&mut self, event: XmlEvent) -> Option<Question> {
+        match event {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => match name.local_name.as_str() {
+                //The root element only holds the questions
+                "questions" => {}
+                "include" if self.cur_question.is_none() => match attr_value(&attributes, "src") {
+                    Some(src) => self.includes.push(src.to_string()),
+                    None => self
+                        .warnings
+                        .push("An include needs a src attribute.".to_string()),
+                },
+                "question" => {
+                    let mut question = Question::new();
+                    self.question_namespace = name.namespace.clone();
+                    question.id = attr_value(&attributes, "id").map(str::to_string);
+                    question.category = attr_value(&attributes, "category").map(str::to_string);
+                    if let Some(value) = attr_value(&attributes, "difficulty") {
+                        match value.trim().parse() {
+                            Ok(difficulty) => question.difficulty = Some(difficulty),
+                            Err(_) => self.warnings.push(unexpected_difficulty(value)),
+                        }
+                    }
+                    if let Some(value) = attr_value(&attributes, "shuffle") {
+                        match value.trim().parse() {
+                            Ok(shuffle) => question.shuffle = shuffle,
+                            Err(_) => self.warnings.push(format!(
+                                "Unexpected shuffle \"{value}\", expected true or false."
+                            )),
+                        }
+                    }
+                    self.cur_question = Some(question)
+                }
+                "prompt" | "correctAnswer" | "incorrectAnswer" | "acceptableAnswer"
+                | "explanation" | "source" | "category" | "difficulty" | "tag"
+                    if is_field(&name, &self.question_namespace) =>
+                {
+                    match self.cur_question {
+                        Some(_) => self.cur_data = Some(String::new()),
+                        None => self
+                            .warnings
+                            .push(unexpected_tag(&name.borrow().to_repr(), false)),
+                    }
+                }
+                _ => self
+                    .warnings
+                    .push(unexpected_tag(&name.borrow().to_repr(), false)),
+            },
+            XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                "question" => match self.cur_question {
+                    Some(_) => return self.cur_question.take(),
+                    None => self.warnings.push(unexpected_tag("question", true)),
+                },
+                "prompt" | "correctAnswer" | "incorrectAnswer" | "acceptableAnswer"
+                | "explanation" | "source" | "category" | "difficulty" | "tag"
+                    if is_field(&name, &self.question_namespace) =>
+                {
+                    match (self.cur_question.as_mut(), self.cur_data.take()) {
+                        (Some(question), Some(data)) => {
+                            if name.local_name == "prompt" {
+                                question.text = data;
+                            } else if name.local_name == "correctAnswer" {
+                                //Repeated correct answers make it a select-all-that-apply question
+                                if question.answer.is_empty() {
+                                    question.answer = data;
+                                } else {
+                                    question.extra_answers.push(data);
+                                }
+                            } else if name.local_name == "incorrectAnswer" {
+                                question.wrong_answers.push(data);
+                            } else if name.local_name == "acceptableAnswer" {
+                                question.acceptable_answers.push(data);
+                            } else if name.local_name == "explanation" {
+                                question.explanation = Some(data);
+                            } else if name.local_name == "source" {
+                                question.source = Some(data);
+                            } else if name.local_name == "category" {
+                                question.category = Some(data);
+                            } else if name.local_name == "difficulty" {
+                                match data.trim().parse() {
+                                    Ok(difficulty) => question.difficulty = Some(difficulty),
+                                    Err(_) => self.warnings.push(unexpected_difficulty(&data)),
+                                }
+                            } else if name.local_name == "tag" {
+                                question.tags.push(data.trim().to_string());
+                            }
+                        }
+                        //Outside of a question, or a field whose text a nested field already took
+                        _ => self
+                            .warnings
+                            .push(unexpected_tag(&name.borrow().to_repr(), true)),
+                    }
+                }
+                _ => {}
+            },
+            XmlEvent::Characters(s) | XmlEvent::CData(s) => match self.cur_data {
+                Some(_) => {
+                    let mut data = self.cur_data.take().unwrap();
+                    data.push_str(s.as_str());
+                    self.cur_data = Some(data);
+                }
+                //Whitespace between elements is fine, anything else is misplaced
+                None => {
+                    if !s.trim().is_empty() {
+                        self.warnings.push(format!(
+                            "Unexpected text \"{}\" outside of a question field.",
+                            s.trim()
+                        ))
+                    }
+                }
+            },
+            //A field that only holds whitespace still keeps it
+            XmlEvent::Whitespace(s) => {
+                if let Some(data) = &mut self.cur_data {
+                    data.push_str(&s);
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+impl<R: Read> Iterator for QuestionReader<R> {
+    type Item = Result<Question, xml::reader::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next()? {
+                Ok(event) => {
+                    if let Some(question) = self.handle(event) {
+                        return Some(Ok(question));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// The value of an attribute without a namespace, those of other namespaces belong to someone
+/// else.
+fn attr_value<'a>(attrs: &'a [OwnedAttribute], name: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|attr| attr.name.namespace.is_none() && attr.name.local_name == name)
+        .map(|attr| attr.value.as_str())
+}
+
+fn is_field(name: &OwnedName, question_namespace: &Option<String>) -> bool {
+    name.namespace.is_none() || name.namespace == *question_namespace
+}
+
+fn unexpected_difficulty(value: &str) -> String {
+    format!("Unexpected difficulty \"{value}\", expected easy, medium or hard.")
+}
+
+fn unexpected_tag(name: &str, closing: bool) -> String {
+    if closing {
+        format!("Unexpected closing {name} tag.")
+    } else {
+        format!("Unexpected {name} tag.")
+    }
+}
+
+/// Sends a single question request to check that the API accepts the configured key.
+///
+/// Returns whether the request was authenticated.
+pub fn check_api(config: &Config, timeout: u64, proxy: Option<&str>) -> bool {
+    if config.api_key.is_none() {
+        println!("No API key is configured, the request is sent without one.");
+    }
+    let res = match api_request(
+        &build_client(timeout, proxy),
+        &format!("{API_URL}?limit=1"),
+        config,
+    )
+    .send()
+    {
+        Ok(res) => res,
+        Err(err) => {
+            println!("Could not reach the API: {err}");
+            return false;
+        }
+    };
+    match res.status() {
+        status if status.is_success() => {
+            println!("Authentication succeeded.");
+            true
+        }
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            println!("Authentication failed: {}", res.status());
+            false
+        }
+        status => {
+            println!("The API responded with {status}.");
+            false
+        }
+    }
+}
+
+fn api_request(client: &Client, url: &str, config: &Config) -> RequestBuilder {
+    let request = client.get(url);
+    match &config.api_key {
+        Some(key) => request.header("X-Api-Key", key),
+        None => request,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use proptest::prelude::*;
+    use proptest::test_runner::{Config as ProptestConfig, RngAlgorithm, TestRng, TestRunner};
+
+    use super::*;
+
+    /// Keeps failures reproducible on CI.
+    const PROPTEST_SEED: [u8; 32] = *b"the_quiz xml round trip seed 001";
+
+    fn parse(xml: &str) -> (Result<Vec<Question>, xml::reader::Error>, Vec<String>) {
+        let mut warnings = Vec::new();
+        let parser = EventReader::new(Cursor::new(xml.as_bytes()));
+        let result = parse_events(parser, &mut warnings, &mut Vec::new());
+        (result, warnings)
+    }
+
+    #[test]
+    fn parses_a_question_with_one_wrong_answer() {
+        let (result, warnings) = parse(
+            "<questions><question>\
+                <prompt>What is 2 + 2?</prompt>\
+                <correctAnswer>4</correctAnswer>\
+                <incorrectAnswer>5</incorrectAnswer>\
+            </question></questions>",
+        );
+        let questions = result.unwrap();
+        assert_eq!(questions.len(), 1);
+        assert_eq!(questions[0].text, "What is 2 + 2?");
+        assert_eq!(questions[0].answer, "4");
+        assert_eq!(questions[0].wrong_answers, ["5"]);
+        assert!(warnings.is_empty(), "{warnings:?}");
+    }
+
+    #[test]
+    fn plain_progress_is_never_redrawn() {
+        let line = progress_line(3, 10, true);
+        assert_eq!(line, "Loading questions... (3/10)\n");
+        assert!(!line.contains(['\r', '\x1b']));
+        assert!(progress_line(3, 10, false).starts_with('\r'));
+    }
+
+    #[test]
+    fn streaming_stops_after_max_questions() {
+        //The broken end of the file is never read, loading all of it would end the program
+        let xml = "<questions>\
+            <question><prompt>One</prompt><correctAnswer>1</correctAnswer></question>\
+            <question><prompt>Two</prompt><correctAnswer>2</correctAnswer></question>\
+            <question><prompt>Three</prompt></wrong>";
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("questions.xml");
+        fs::write(&path, xml).unwrap();
+        let questions = read_first_questions(&path, 2, false);
+        let prompts: Vec<&str> = questions.iter().map(|q| q.text.as_str()).collect();
+        assert_eq!(prompts, ["One", "Two"]);
+    }
+
+    #[test]
+    fn streaming_hands_over_the_includes() {
+        let xml = "<questions><include src=\"more.xml\"/>\
+            <question><prompt>One</prompt><correctAnswer>1</correctAnswer></question>\
+            </questions>";
+        let mut includes = Vec::new();
+        let parser = EventReader::new(Cursor::new(xml.as_bytes()));
+        let questions: Vec<Question> = parse_data_streaming(parser, 5, &mut includes)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(questions.len(), 1);
+        assert_eq!(includes, ["more.xml"]);
+    }
+
+    #[test]
+    fn streaming_goes_on_with_the_includes_when_the_file_runs_out() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("questions.xml");
+        fs::write(
+            &path,
+            "<questions><include src=\"more.xml\"/>\
+                <question><prompt>One</prompt><correctAnswer>1</correctAnswer></question>\
+            </questions>",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("more.xml"),
+            "<questions>\
+                <question><prompt>Two</prompt><correctAnswer>2</correctAnswer></question>\
+                <question><prompt>Three</prompt><correctAnswer>3</correctAnswer></question>\
+            </questions>",
+        )
+        .unwrap();
+        let questions = read_first_questions(&path, 2, false);
+        let prompts: Vec<&str> = questions.iter().map(|q| q.text.as_str()).collect();
+        assert_eq!(prompts, ["One", "Two"]);
+        //The same questions the whole file starts with
+        let all = get_questions_from_path(&path, false);
+        assert_eq!(questions, all[..2]);
+    }
+
+    #[test]
+    fn parses_multiple_wrong_answers() {
+        let (result, _) = parse(
+            "<questions><question>\
+                <prompt>Which is a primary color?</prompt>\
+                <correctAnswer>Red</correctAnswer>\
+                <incorrectAnswer>Green</incorrectAnswer>\
+                <incorrectAnswer>Orange</incorrectAnswer>\
+                <incorrectAnswer>Purple</incorrectAnswer>\
+            </question></questions>",
+        );
+        let questions = result.unwrap();
+        assert_eq!(questions[0].wrong_answers, ["Green", "Orange", "Purple"]);
+    }
+
+    #[test]
+    fn an_empty_file_is_an_error() {
+        let (result, _) = parse("");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn a_root_without_questions_has_no_questions() {
+        let (result, warnings) = parse("<questions></questions>");
+        assert!(result.unwrap().is_empty());
+        assert!(warnings.is_empty(), "{warnings:?}");
+    }
+
+    #[test]
+    fn warns_about_unexpected_tags() {
+        let (result, warnings) = parse(
+            "<questions><question>\
+                <prompt>Q</prompt><correctAnswer>A</correctAnswer>\
+                <incorrectAnswer>B</incorrectAnswer><hint/>\
+            </question></questions>",
+        );
+        assert_eq!(result.unwrap().len(), 1);
+        assert_eq!(warnings, ["Unexpected hint tag."]);
+    }
+
+    #[test]
+    fn warns_about_text_outside_of_fields() {
+        let (result, warnings) = parse(
+            "<questions><question>stray\
+                <prompt>Q</prompt><correctAnswer>A</correctAnswer>\
+                <incorrectAnswer>B</incorrectAnswer>\
+            </question></questions>",
+        );
+        assert_eq!(result.unwrap().len(), 1);
+        assert_eq!(
+            warnings,
+            ["Unexpected text \"stray\" outside of a question field."]
+        );
+    }
+
+    #[test]
+    fn warns_about_nested_fields() {
+        let (result, warnings) = parse(
+            "<questions><question>\
+                <prompt><correctAnswer>A</correctAnswer></prompt>\
+            </question></questions>",
+        );
+        assert_eq!(result.unwrap()[0].answer, "A");
+        assert_eq!(warnings, ["Unexpected closing prompt tag."]);
+    }
+
+    #[test]
+    fn reads_namespaced_questions() {
+        let (result, warnings) = parse(
+            "<quiz:questions xmlns:quiz=\"https://example.com/quiz\">\
+                <quiz:question quiz:note=\"ignored\" id=\"q1\">\
+                    <quiz:prompt>Q</quiz:prompt><quiz:correctAnswer>A</quiz:correctAnswer>\
+                    <quiz:incorrectAnswer>B</quiz:incorrectAnswer>\
+                </quiz:question>\
+            </quiz:questions>",
+        );
+        let questions = result.unwrap();
+        assert_eq!(questions[0].id.as_deref(), Some("q1"));
+        assert_eq!(questions[0].text, "Q");
+        assert_eq!(questions[0].answer, "A");
+        assert_eq!(questions[0].wrong_answers, ["B"]);
+        assert!(warnings.is_empty(), "{warnings:?}");
+    }
+
+    #[test]
+    fn skips_fields_of_other_namespaces() {
+        let (result, warnings) = parse(
+            "<questions xmlns=\"https://example.com/quiz\" xmlns:x=\"https://example.com/x\">\
+                <question><prompt>Q</prompt><correctAnswer>A</correctAnswer>\
+                <incorrectAnswer>B</incorrectAnswer><x:incorrectAnswer/></question>\
+            </questions>",
+        );
+        assert_eq!(result.unwrap()[0].wrong_answers, ["B"]);
+        assert_eq!(warnings, ["Unexpected x:incorrectAnswer tag."]);
+    }
+
+    #[test]
+    fn reads_cdata_as_text() {
+        let (result, warnings) = parse(
+            "<questions><question>\
+                <prompt>What does <![CDATA[if a < b && b > c {}]]> check?</prompt>\
+                <correctAnswer><![CDATA[<b>]]></correctAnswer>\
+                <incorrectAnswer>B</incorrectAnswer>\
+            </question></questions>",
+        );
+        let questions = result.unwrap();
+        assert_eq!(questions[0].text, "What does if a < b && b > c {} check?");
+        assert_eq!(questions[0].answer, "<b>");
+        assert!(warnings.is_empty(), "{warnings:?}");
+    }
+
+    #[test]
+    fn reads_question_attributes() {
+        let (result, warnings) = parse(
+            "<questions>\
+                <question difficulty=\"hard\" category=\"Science\">\
+                    <prompt>Q</prompt><correctAnswer>A</correctAnswer>\
+                    <incorrectAnswer>B</incorrectAnswer>\
+                </question>\
+                <question difficulty=\"brutal\">\
+                    <prompt>Q2</prompt><correctAnswer>A</correctAnswer>\
+                    <incorrectAnswer>B</incorrectAnswer>\
+                </question>\
+            </questions>",
+        );
+        let questions = result.unwrap();
+        assert_eq!(questions[0].difficulty, Some(Difficulty::Hard));
+        assert_eq!(questions[0].category.as_deref(), Some("Science"));
+        assert_eq!(questions[1].difficulty, None);
+        assert_eq!(
+            warnings,
+            ["Unexpected difficulty \"brutal\", expected easy, medium or hard."]
+        );
+    }
+
+    #[test]
+    fn reads_multi_line_explanations() {
+        let (result, warnings) = parse(
+            "<questions><question>\
+                <prompt>Q</prompt><correctAnswer>A</correctAnswer>\
+                <incorrectAnswer>B</incorrectAnswer>\
+                <explanation>First line,\nsecond &amp; last line.</explanation>\
+            </question></questions>",
+        );
+        assert_eq!(
+            result.unwrap()[0].explanation.as_deref(),
+            Some("First line,\nsecond & last line.")
+        );
+        assert!(warnings.is_empty(), "{warnings:?}");
+    }
+
+    const ONE_QUESTION: &str = "<questions><question>\
+        <prompt>Q</prompt><correctAnswer>A</correctAnswer><incorrectAnswer>B</incorrectAnswer>\
+        </question></questions>";
+
+    #[test]
+    fn reads_utf16_files_by_their_byte_order_mark() {
+        let xml = "<?xml version=\"1.0\" encoding=\"UTF-16\"?><questions><question>\
+            <prompt>Was ist größer?</prompt><correctAnswer>Die Sonne</correctAnswer>\
+            <incorrectAnswer>Der Mond</incorrectAnswer></question></questions>";
+        let encodings: [(&[u8], fn(u16) -> [u8; 2]); 2] = [
+            (b"\xFF\xFE", u16::to_le_bytes),
+            (b"\xFE\xFF", u16::to_be_bytes),
+        ];
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("questions.xml");
+        for (bom, encode) in encodings {
+            let mut bytes = bom.to_vec();
+            bytes.extend(xml.encode_utf16().flat_map(encode));
+            fs::write(&path, bytes).unwrap();
+
+            let questions = get_questions_from_path(&path, false);
+            assert_eq!(questions.len(), 1);
+            assert_eq!(questions[0].text, "Was ist größer?");
+            assert_eq!(questions[0].answer, "Die Sonne");
+        }
+    }
+
+    #[test]
+    fn follows_includes_relative_to_the_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("more")).unwrap();
+        fs::write(
+            dir.path().join("main.xml"),
+            "<questions><include src=\"more/extra.xml\"/></questions>",
+        )
+        .unwrap();
+        fs::write(dir.path().join("more/extra.xml"), ONE_QUESTION).unwrap();
+
+        let questions =
+            load_xml_with_includes(&dir.path().join("main.xml"), false, &mut HashSet::new())
+                .unwrap();
+        assert_eq!(questions.len(), 1);
+    }
+
+    #[test]
+    fn rejects_circular_includes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("a.xml"),
+            "<questions><include src=\"b.xml\"/></questions>",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("b.xml"),
+            "<questions><include src=\"a.xml\"/></questions>",
+        )
+        .unwrap();
+
+        let result = load_xml_with_includes(&dir.path().join("a.xml"), false, &mut HashSet::new());
+        assert!(result.unwrap_err().contains("circle"));
+    }
+
+    #[test]
+    fn rejects_missing_includes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("main.xml"),
+            "<questions><include src=\"gone.xml\"/></questions>",
+        )
+        .unwrap();
+
+        let result =
+            load_xml_with_includes(&dir.path().join("main.xml"), false, &mut HashSet::new());
+        assert!(result.unwrap_err().starts_with("Could not find"));
+    }
+
+    #[test]
+    fn reports_malformed_xml() {
+        let (result, _) = parse("<questions><question><prompt>Q</question></questions>");
+        assert!(result.is_err());
+    }
+
+    fn question_strategy() -> impl Strategy<Value = Question> {
+        let text = "[ -~]*";
+        (text, text, prop::collection::vec(text, 0..20)).prop_map(
+            |(text, answer, wrong_answers)| Question {
+                text,
+                answer,
+                wrong_answers,
+                ..Question::new()
+            },
+        )
+    }
+
+    #[test]
+    fn questions_survive_an_xml_round_trip() {
+        let mut runner = TestRunner::new_with_rng(
+            ProptestConfig::default(),
+            TestRng::from_seed(RngAlgorithm::ChaCha, &PROPTEST_SEED),
+        );
+        let strategy = prop::collection::vec(question_strategy(), 0..10);
+        runner
+            .run(&strategy, |questions| {
+                let mut xml = Vec::new();
+                serialize_questions_to_xml(&questions, &mut xml).unwrap();
+                let (parsed, warnings) = parse(&String::from_utf8(xml).unwrap());
+                prop_assert_eq!(parsed.unwrap(), questions);
+                prop_assert!(warnings.is_empty(), "{:?}", warnings);
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+use chrono::{Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::dirs::app_dirs;
+use crate::game::{Outcome, QuestionResult};
+use crate::question::Question;
+
+const SCHEDULE_FILENAME: &str = "review_schedule.json";
+/// The ease every question starts out with.
+const INITIAL_EASE: f64 = 2.5;
+/// The ease never drops below this, or hard questions would come back every day forever.
+const MIN_EASE: f64 = 1.3;
+
+/// When a question is due again, following the SM-2 algorithm.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReviewState {
+    /// Correct answers in a row.
+    pub repetitions: u32,
+    pub interval_days: u64,
+    pub ease: f64,
+    pub due: NaiveDate,
+}
+
+impl ReviewState {
+    fn new(today: NaiveDate) -> ReviewState {
+        ReviewState {
+            repetitions: 0,
+            interval_days: 0,
+            ease: INITIAL_EASE,
+            due: today,
+        }
+    }
+
+    /// Schedules the next review. `quality` rates the answer from 0 (blackout) to 5 (perfect).
+    fn review(&mut self, quality: u32, today: NaiveDate) {
+        if quality >= 3 {
+            self.interval_days = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval_days as f64 * self.ease).round() as u64,
+            };
+            self.repetitions += 1;
+        } else {
+            self.repetitions = 0;
+            self.interval_days = 1;
+        }
+        let miss = f64::from(5 - quality.min(5));
+        self.ease = (self.ease + 0.1 - miss * (0.08 + miss * 0.02)).max(MIN_EASE);
+        self.due = today + Days::new(self.interval_days);
+    }
+}
+
+fn quality(outcome: Outcome) -> u32 {
+    match outcome {
+        Outcome::Correct => 5,
+        Outcome::Partial => 3,
+        Outcome::Incorrect => 1,
+        Outcome::Skipped | Outcome::TimedOut => 0,
+    }
+}
+
+fn schedule_path() -> io::Result<PathBuf> {
+    Ok(app_dirs().profile_dir()?.join(SCHEDULE_FILENAME))
+}
+
+fn load_schedule() -> io::Result<HashMap<String, ReviewState>> {
+    let contents = match fs::read_to_string(schedule_path()?) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(err),
+    };
+    serde_json::from_str(&contents).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+}
+
+/// Moves the questions that are due for review to the front, the longest overdue first.
+///
+/// New questions come next and the ones that aren't due yet last. The sort is stable, so
+/// questions that are due on the same day keep their order.
+pub fn sort_by_due(questions: &mut [Question]) {
+    let schedule = load_schedule().unwrap_or_else(|err| {
+        println!("Warning: could not read the review schedule: {err}");
+        HashMap::new()
+    });
+    let today = Local::now().date_naive();
+    questions.sort_by_key(|question| match schedule.get(&question.stats_key()) {
+        Some(state) if state.due <= today => (0, state.due),
+        None => (1, today),
+        Some(state) => (2, state.due),
+    });
+}
+
+/// Reschedules the questions of a finished game.
+pub fn update_review_schedule(results: &[QuestionResult]) {
+    let today = Local::now().date_naive();
+    let result = load_schedule().and_then(|mut schedule| {
+        for result in results {
+            schedule
+                .entry(result.question.stats_key())
+                .or_insert_with(|| ReviewState::new(today))
+                .review(quality(result.outcome), today);
+        }
+        let json = serde_json::to_string_pretty(&schedule)
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+        fs::write(schedule_path()?, json)
+    });
+    if let Err(err) = result {
+        println!("Warning: could not update the review schedule: {err}");
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::question::{normalize_text, Question};
+use crate::source::get_questions_from_path;
+
+/// Something wrong with one of the questions of a file.
+pub struct Issue {
+    /// The position of the question in the file, starting at 0.
+    pub index: usize,
+    pub message: String,
+    /// Whether the question can still be played, as the game shows a doubled answer only once.
+    pub playable: bool,
+}
+
+/// Checks the questions for missing fields and duplicate answers.
+pub fn validate_questions(questions: &[Question]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for (index, question) in questions.iter().enumerate() {
+        let mut report = |message: &str, playable: bool| {
+            issues.push(Issue {
+                index,
+                message: message.to_string(),
+                playable,
+            })
+        };
+        if question.text.trim().is_empty() {
+            report("is missing a <prompt> element", false);
+        }
+        if question.answer.trim().is_empty() {
+            report("is missing a <correctAnswer> element", false);
+        }
+        if question.wrong_answers.is_empty() {
+            report(
+                "has no <incorrectAnswer> elements, it needs at least two options",
+                false,
+            );
+        }
+        let mut seen: Vec<String> = Vec::new();
+        let options = question
+            .correct_answers()
+            .into_iter()
+            .chain(question.wrong_answers.iter().map(String::as_str));
+        for option in options {
+            let option = normalize_text(option);
+            if option.is_empty() {
+                continue;
+            }
+            if seen.contains(&option) {
+                report(
+                    &format!("lists the answer \"{option}\" more than once"),
+                    true,
+                );
+            } else {
+                seen.push(option);
+            }
+        }
+    }
+    issues
+}
+
+/// Warns about every question that can't be played and leaves those out. Doubled answers are
+/// left for the game to warn about.
+///
+/// In strict mode any problem ends the program instead.
+pub fn remove_invalid(questions: Vec<Question>, strict: bool) -> Vec<Question> {
+    let mut issues = validate_questions(&questions);
+    if !strict {
+        issues.retain(|issue| !issue.playable);
+    }
+    if issues.is_empty() {
+        return questions;
+    }
+    for issue in &issues {
+        println!("Question {} {}", issue.index + 1, issue.message);
+    }
+    if strict {
+        println!("Found {} problems. Exiting.", issues.len());
+        std::process::exit(1);
+    }
+    questions
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| !issues.iter().any(|issue| issue.index == *index))
+        .map(|(_, question)| question)
+        .collect()
+}
+
+/// Prints a report on the questions in the file. Exits with 1 if anything is wrong with them.
+pub fn run_validate(path: &Path) {
+    let questions = get_questions_from_path(path, false);
+    println!("{}: {} questions", path.display(), questions.len());
+    let issues = validate_questions(&questions);
+    for issue in &issues {
+        println!("Question {} {}", issue.index + 1, issue.message);
+    }
+    if issues.is_empty() {
+        println!("No problems found.");
+    } else {
+        println!("Found {} problems.", issues.len());
+        std::process::exit(1);
+    }
+}
+
+/// Prints how many valid questions the file holds, by category if the questions have one.
+pub fn run_count(path: &Path) {
+    let questions = get_questions_from_path(path, false);
+    let issues = validate_questions(&questions);
+    let valid: Vec<&Question> = questions
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !issues.iter().any(|issue| issue.index == *index))
+        .map(|(_, question)| question)
+        .collect();
+    println!("{}: {} questions", path.display(), valid.len());
+    if valid.len() < questions.len() {
+        println!(
+            "{} more have problems, run `validate` to see them.",
+            questions.len() - valid.len()
+        );
+    }
+    if valid.iter().all(|question| question.category.is_none()) {
+        return;
+    }
+    let mut categories: BTreeMap<&str, usize> = BTreeMap::new();
+    for question in &valid {
+        let category = question.category.as_deref().unwrap_or("Uncategorized");
+        *categories.entry(category).or_default() += 1;
+    }
+    for (category, count) in categories {
+        println!("{count:>6}  {category}");
+    }
+}
@@ -0,0 +1,79 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Outcome, QuestionResult};
+use crate::question::{normalize_text, Question};
+use crate::source::NO_QUESTIONS_EXIT_CODE;
+
+/// A wrongly answered question, stored as one line of JSON.
+#[derive(Debug, Deserialize, Serialize)]
+struct WrongAnswer {
+    question: String,
+    correct_answer: String,
+    chosen_answer: String,
+    #[serde(default)]
+    wrong_answers: Vec<String>,
+}
+
+/// Appends every wrongly answered question of the game to the log at `path`.
+pub fn append_wrong_answers(path: &Path, results: &[QuestionResult]) {
+    let write = || -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        for result in results.iter().filter(|r| r.outcome == Outcome::Incorrect) {
+            let entry = WrongAnswer {
+                question: result.question.text.clone(),
+                correct_answer: result.question.correct_answers().join(", "),
+                chosen_answer: result.chosen.join(", "),
+                wrong_answers: result.question.wrong_answers.clone(),
+            };
+            let line = serde_json::to_string(&entry)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            writeln!(file, "{line}")?;
+        }
+        Ok(())
+    };
+    if let Err(err) = write() {
+        println!("Warning: could not write to {}: {err}", path.display());
+    }
+}
+
+/// Turns a wrong answer log back into questions, asking each one only once.
+pub fn load_wrong_log(path: &Path) -> Vec<Question> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            println!("Could not read {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    };
+    let mut questions: Vec<Question> = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: WrongAnswer = match serde_json::from_str(line) {
+            Ok(entry) => entry,
+            Err(err) => {
+                println!("Skipping line {} of {}: {err}", index + 1, path.display());
+                continue;
+            }
+        };
+        let key = normalize_text(&entry.question);
+        if questions.iter().any(|q| normalize_text(&q.text) == key) {
+            continue;
+        }
+        let mut question = Question::new();
+        question.text = entry.question;
+        question.answer = entry.correct_answer;
+        question.wrong_answers = entry.wrong_answers;
+        questions.push(question);
+    }
+    if questions.is_empty() {
+        println!("No questions were found in `{}`.", path.display());
+        std::process::exit(NO_QUESTIONS_EXIT_CODE);
+    }
+    questions
+}
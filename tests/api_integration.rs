@@ -0,0 +1,144 @@
+use httpmock::prelude::*;
+use the_quiz::config::Config;
+use the_quiz::error::QuizError;
+use the_quiz::source::{
+    fetch_questions, get_questions_from_api, ApiQuery, LazyApiSource, QuestionSource,
+};
+
+const QUESTIONS: &str = r#"[
+    {
+        "category": "Geography",
+        "id": "622a1c357cc59eab6f94fa8f",
+        "correctAnswer": "Canberra",
+        "incorrectAnswers": ["Sydney", "Melbourne", "Perth"],
+        "question": "What is the capital of Australia?",
+        "difficulty": "easy"
+    }
+]"#;
+
+fn query(server: &MockServer) -> ApiQuery {
+    ApiQuery {
+        base_url: server.url("/api/questions"),
+        limit: Some(1),
+        difficulty: None,
+        category: None,
+        timeout: 5,
+        fallback_file: None,
+        proxy: None,
+        debug_requests: false,
+        parallel: 3,
+        page_size: 50,
+        cache_ttl: None,
+        plain: false,
+    }
+}
+
+#[test]
+fn returns_the_questions_of_the_api() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/api/questions")
+            .query_param("limit", "1");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(QUESTIONS);
+    });
+
+    let questions = get_questions_from_api(&Config::default(), &query(&server));
+
+    mock.assert();
+    assert_eq!(questions.len(), 1);
+    let question = &questions[0];
+    assert_eq!(question.id.as_deref(), Some("622a1c357cc59eab6f94fa8f"));
+    assert_eq!(question.text, "What is the capital of Australia?");
+    assert_eq!(question.answer, "Canberra");
+    assert_eq!(question.wrong_answers, ["Sydney", "Melbourne", "Perth"]);
+    assert_eq!(question.category.as_deref(), Some("Geography"));
+}
+
+#[test]
+fn retries_when_the_server_fails() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/api/questions");
+        then.status(500);
+    });
+
+    let result = fetch_questions(&Config::default(), &query(&server));
+
+    mock.assert_hits(3);
+    match result {
+        Err(QuizError::Http(err)) => {
+            assert_eq!(err.status().map(|status| status.as_u16()), Some(500))
+        }
+        other => panic!("expected a server error, got {other:?}"),
+    }
+}
+
+#[test]
+fn lazy_source_stops_when_batches_repeat() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/api/questions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(QUESTIONS);
+    });
+
+    let mut source = LazyApiSource::new(&Config::default(), query(&server));
+
+    let question = source
+        .next_question()
+        .expect("the first batch has a question");
+    assert_eq!(question.answer, "Canberra");
+    //The next batch only repeats it, so there is nothing more to play
+    assert!(source.next_question().is_none());
+    mock.assert_hits(2);
+}
+
+#[test]
+fn sends_the_pages_of_a_large_limit_together() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/api/questions");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(QUESTIONS);
+    });
+
+    let query = ApiQuery {
+        limit: Some(120),
+        ..query(&server)
+    };
+    let questions = fetch_questions(&Config::default(), &query).unwrap();
+
+    //Three pages go out at once, and as they all repeat the same question no more follow
+    mock.assert_hits(3);
+    assert_eq!(questions.len(), 1);
+}
+
+#[test]
+fn asks_for_at_most_a_page_per_request() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/api/questions")
+            .query_param("limit", "2");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(QUESTIONS);
+    });
+
+    let query = ApiQuery {
+        limit: Some(4),
+        page_size: 2,
+        parallel: 1,
+        ..query(&server)
+    };
+    let questions = fetch_questions(&Config::default(), &query).unwrap();
+
+    //The second page only repeats the first, which ends the loading
+    mock.assert_hits(2);
+    assert_eq!(questions.len(), 1);
+}
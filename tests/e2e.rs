@@ -0,0 +1,250 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+use tempfile::TempDir;
+
+const QUESTIONS: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<questions>
+<question>
+    <prompt>What word is used in the NATO Phonetic Alphabet for the letter D?</prompt>
+    <correctAnswer>Delta</correctAnswer>
+    <incorrectAnswer>Dog</incorrectAnswer>
+    <incorrectAnswer>Danube</incorrectAnswer>
+</question>
+<question>
+    <prompt>Who makes Macintosh computers?</prompt>
+    <correctAnswer>Apple</correctAnswer>
+    <incorrectAnswer>Microsoft</incorrectAnswer>
+    <incorrectAnswer>Google</incorrectAnswer>
+</question>
+</questions>
+"#;
+
+/// Creates a directory holding the question file, which also stands in for the home directory
+/// so the quiz doesn't pick up the config and saved games of whoever runs the tests.
+fn quiz_dir(questions: &str) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("questions.xml"), questions).unwrap();
+    dir
+}
+
+/// The quiz, running in the directory with it as the home directory.
+fn quiz(dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_the_quiz"));
+    command
+        .current_dir(dir)
+        .env("HOME", dir)
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env("XDG_DATA_HOME", dir.join("data"))
+        .env("XDG_CACHE_HOME", dir.join("cache"))
+        .env("LANG", "en_US.UTF-8");
+    command
+}
+
+/// Plays a game from the question file, answering with the given lines.
+fn run_quiz(dir: &Path, answers: &str) -> Output {
+    let mut child = quiz(dir)
+        .args([
+            "--source",
+            "file",
+            "--no-shuffle",
+            "--no-shuffle-questions",
+            "--seed",
+            "0",
+            "--no-color",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(answers.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn plays_a_scripted_game() {
+    let dir = quiz_dir(QUESTIONS);
+    //Without shuffling the correct answer is always the first option
+    let output = run_quiz(dir.path(), "1\n2\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{stdout}");
+    let correct = stdout.find("Correct!").expect(&stdout);
+    let wrong = stdout
+        .find("Wrong! The correct answer is: Apple")
+        .expect(&stdout);
+    assert!(correct < wrong, "{stdout}");
+    assert!(
+        stdout.contains("That's it! You answered 1 questions correctly and 1 incorrectly."),
+        "{stdout}"
+    );
+    assert!(stdout.contains("You scored 10 points."), "{stdout}");
+}
+
+#[test]
+fn stops_on_an_empty_question_file() {
+    let dir = quiz_dir("<questions></questions>");
+    let output = run_quiz(dir.path(), "");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(output.status.code(), Some(2), "{stdout}");
+    assert!(
+        stdout.contains("No questions were found in `questions.xml`"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn stats_sum_up_the_played_games() {
+    let dir = quiz_dir(QUESTIONS);
+    let output = quiz(dir.path()).arg("stats").output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("No games have been played yet."),
+        "{stdout}"
+    );
+
+    run_quiz(dir.path(), "1\n2\n");
+    run_quiz(dir.path(), "1\n1\n");
+    let output = quiz(dir.path()).arg("stats").output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("Games played:       2"), "{stdout}");
+    assert!(stdout.contains("Questions answered: 4"), "{stdout}");
+    assert!(stdout.contains("Accuracy:           75%"), "{stdout}");
+}
+
+#[test]
+fn shows_and_logs_a_drawn_seed() {
+    let dir = quiz_dir(QUESTIONS);
+    let mut child = quiz(dir.path())
+        .args(["--source", "file", "--no-color"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"1\n1\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let seed = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Session seed: "))
+        .expect(&stdout);
+
+    let output = quiz(dir.path()).arg("history").output().unwrap();
+    let history = String::from_utf8_lossy(&output.stdout);
+    assert!(history.contains(&format!("seed {seed}")), "{history}");
+}
+
+#[test]
+fn non_interactive_reads_answers_without_pauses_or_timer() {
+    let dir = quiz_dir(QUESTIONS);
+    let mut child = quiz(dir.path())
+        .args([
+            "--source",
+            "file",
+            "--no-shuffle",
+            "--no-shuffle-questions",
+            "--seed",
+            "0",
+            "--non-interactive",
+            "--confirm-answers",
+            "--clear-screen",
+            "--timer",
+            "1",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"1\n2\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{stdout}");
+    assert!(!stdout.contains('\x1b'), "{stdout:?}");
+    assert!(!stdout.contains('\r'), "{stdout:?}");
+    assert!(!stdout.contains("seconds"), "{stdout}");
+    assert!(!stdout.contains("lock it in"), "{stdout}");
+    assert!(!stdout.contains("Previous question"), "{stdout}");
+    //Each verdict follows its own question
+    let first = stdout.find("Delta").expect(&stdout);
+    let correct = stdout.find("Correct!").expect(&stdout);
+    let second = stdout.find("Macintosh").expect(&stdout);
+    let wrong = stdout.find("Wrong!").expect(&stdout);
+    assert!(
+        first < correct && correct < second && second < wrong,
+        "{stdout}"
+    );
+}
+
+#[test]
+fn refuses_to_delete_outside_the_profiles() {
+    let dir = quiz_dir(QUESTIONS);
+    run_quiz(dir.path(), "1\n1\n");
+    for name in ["..", "../..", "/tmp", ""] {
+        let output = quiz(dir.path())
+            .args(["profiles", "delete", name])
+            .stdin(Stdio::piped())
+            .output()
+            .unwrap();
+        assert!(!output.status.success(), "{name}");
+    }
+    assert!(dir.path().join("data").is_dir());
+}
+
+#[test]
+fn plays_questions_that_list_an_answer_twice() {
+    let dir = quiz_dir(
+        r#"<questions>
+<question>
+    <prompt>What word is used in the NATO Phonetic Alphabet for the letter D?</prompt>
+    <correctAnswer>Delta</correctAnswer>
+    <incorrectAnswer>Dog</incorrectAnswer>
+    <incorrectAnswer>Dog</incorrectAnswer>
+</question>
+</questions>"#,
+    );
+    let output = run_quiz(dir.path(), "1\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{stdout}");
+    assert!(
+        stdout.contains("lists Dog more than once, showing it once."),
+        "{stdout}"
+    );
+    assert_eq!(stdout.matches("Dog").count(), 2, "{stdout}");
+    assert!(stdout.contains("Correct!"), "{stdout}");
+}
+
+#[test]
+fn plays_a_prompt_only_once_even_with_different_ids() {
+    let dir = quiz_dir(
+        r#"<questions>
+<question id="apple-1">
+    <prompt>Who makes Macintosh computers?</prompt>
+    <correctAnswer>Apple</correctAnswer>
+    <incorrectAnswer>Microsoft</incorrectAnswer>
+</question>
+<question id="apple-2">
+    <prompt>  who makes  Macintosh computers?</prompt>
+    <correctAnswer>Apple</correctAnswer>
+    <incorrectAnswer>Google</incorrectAnswer>
+</question>
+</questions>"#,
+    );
+    let output = run_quiz(dir.path(), "1\n1\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("Removed 1 duplicate question."), "{stdout}");
+    assert_eq!(stdout.matches("Correct!").count(), 1, "{stdout}");
+}